@@ -0,0 +1,86 @@
+//! Probing of many devices with a single reused prober
+use crate::{
+    cancel::CancellationToken,
+    probe_result::ProbeResult,
+    prober::Prober,
+    scan::{configure, safe_probe_result, ScannedDevice},
+    BlkIdResult, OpenFlags,
+};
+use std::{
+    os::unix::io::{AsFd, AsRawFd},
+    path::Path,
+};
+
+/// Prober configured once and reused for many devices.
+///
+/// Every call assigns the next device by [`Prober::set_device`], which resets the buffers and the
+/// probing state but keeps the chains configuration (flags and filters). It saves the prober
+/// allocation and chains setup for every device of large scans.
+///
+/// # Examples
+///
+/// ```ignore, compile_fail
+/// let mut batch = BatchProber::new()?;
+/// batch.prober().set_superblocks_flags(SuperblocksFlags::TYPE | SuperblocksFlags::UUID)?;
+///
+/// for path in paths {
+///     let device = batch.probe_path(path)?;
+///     println!("{:?}", device.result().uuid());
+/// }
+/// ```
+pub struct BatchProber {
+    prober: Prober,
+    open_flags: OpenFlags,
+    token: Option<CancellationToken>,
+}
+
+impl BatchProber {
+    /// Creates prober with superblocks (including `USAGE`) and partitions chains enabled, the same
+    /// configuration as [`crate::scan`] uses
+    pub fn new() -> BlkIdResult<Self> {
+        let prober = Prober::new()?;
+        configure(&prober)?;
+
+        Ok(Self {
+            prober,
+            open_flags: OpenFlags::empty(),
+            token: None,
+        })
+    }
+
+    /// The reused prober, use it to change the chains configuration (flags, filters)
+    pub fn prober(&self) -> &Prober {
+        &self.prober
+    }
+
+    /// Flags used by [`Self::probe_path`] to open devices
+    pub fn open_flags(mut self, flags: OpenFlags) -> Self {
+        self.open_flags = flags;
+        self
+    }
+
+    /// Makes every probe call fail with [`crate::BlkIdError::Cancelled`] once `token` is cancelled
+    pub fn cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.token = Some(token);
+        self
+    }
+
+    /// Probes the next device (or image file) by an already open descriptor. The descriptor has
+    /// to stay open during the call only.
+    pub fn probe_next<F: AsFd>(&mut self, fd: F) -> BlkIdResult<ProbeResult> {
+        if let Some(token) = &self.token {
+            token.check()?;
+        }
+
+        self.prober.set_device(fd.as_fd().as_raw_fd(), 0, None)?;
+        safe_probe_result(&self.prober)
+    }
+
+    /// Opens the next device (or image file) and probes it
+    pub fn probe_path<P: AsRef<Path>>(&mut self, path: P) -> BlkIdResult<ScannedDevice> {
+        let path = path.as_ref();
+        let file = self.open_flags.open(path)?;
+        let result = self.probe_next(&file)?;
+        Ok(ScannedDevice::new(path, result))
+    }
+}
@@ -0,0 +1,262 @@
+//! Inventory of all block devices in CSV or JSON format
+//!
+//! Usage: `blkid-inventory [--format csv|json]`
+use blkid::{
+    device::Device,
+    devno::Devno,
+    report::{FullReport, TopologyInfo},
+    sysfs::{list_block_devices, DeviceInfo},
+    system_roots,
+    tag::{PartitionTag, SuperblockTag, TagType},
+    BlkIdResult,
+};
+use std::{collections::HashMap, env, fs, process, str::FromStr};
+
+const CSV_HEADER: &str = "path,size,logical_sector_size,physical_sector_size,minimum_io_size,\
+                          optimal_io_size,alignment_offset,pt_type,partitions,fs_type,label,uuid,\
+                          mountpoint";
+
+#[derive(Clone, Copy)]
+enum Format {
+    Csv,
+    Json,
+}
+
+struct Entry {
+    path: String,
+    size: u64,
+    report: Option<FullReport>,
+    mountpoint: Option<String>,
+}
+
+impl Entry {
+    fn topology(&self) -> Option<&TopologyInfo> {
+        self.report.as_ref()?.topology()
+    }
+
+    fn value(&self, tag: impl Into<TagType>) -> Option<String> {
+        self.report.as_ref()?.values().get(tag).map(str::to_owned)
+    }
+}
+
+fn main() {
+    let format = match parse_args() {
+        Some(format) => format,
+        None => {
+            eprintln!("usage: blkid-inventory [--format csv|json]");
+            process::exit(2);
+        }
+    };
+
+    if let Err(err) = run(format) {
+        eprintln!("blkid-inventory: {}", err);
+        process::exit(1);
+    }
+}
+
+fn parse_args() -> Option<Format> {
+    let mut args = env::args().skip(1);
+    let mut format = Format::Csv;
+
+    while let Some(arg) = args.next() {
+        let value = match arg.as_str() {
+            "--format" => args.next()?,
+            _ => arg.strip_prefix("--format=")?.to_owned(),
+        };
+        format = match value.as_str() {
+            "csv" => Format::Csv,
+            "json" => Format::Json,
+            _ => return None,
+        };
+    }
+
+    Some(format)
+}
+
+fn run(format: Format) -> BlkIdResult<()> {
+    let mounts = mountpoints();
+    let entries = list_block_devices()?
+        .iter()
+        .map(|info| inventory(info, &mounts))
+        .collect::<Vec<_>>();
+
+    match format {
+        Format::Csv => print_csv(&entries),
+        Format::Json => print_json(&entries),
+    }
+    Ok(())
+}
+
+/// Collects the inventory of a device. Devices which cannot be opened or probed are reported with
+/// the sysfs values only and a warning on stderr.
+fn inventory(info: &DeviceInfo, mounts: &HashMap<Devno, String>) -> Entry {
+    let report = match Device::open(info.path()) {
+        Ok(device) => Some(device.report().clone()),
+        Err(err) => {
+            eprintln!("blkid-inventory: {}: {}", info.path().display(), err);
+            None
+        }
+    };
+
+    Entry {
+        path: info.path().display().to_string(),
+        size: info.size(),
+        report,
+        mountpoint: mounts.get(&info.devno()).cloned(),
+    }
+}
+
+/// Mount points by device number, the first mount wins for devices mounted several times
+fn mountpoints() -> HashMap<Devno, String> {
+    let mut mounts = HashMap::new();
+    let mountinfo = system_roots().get_proc().join("self/mountinfo");
+    let content = fs::read_to_string(mountinfo).unwrap_or_default();
+
+    for line in content.lines() {
+        let mut fields = line.split(' ').skip(2);
+        let (devno, mountpoint) = match (fields.next(), fields.nth(1)) {
+            (Some(devno), Some(mountpoint)) => (devno, mountpoint),
+            _ => continue,
+        };
+        if let Ok(devno) = Devno::from_str(devno) {
+            mounts
+                .entry(devno)
+                .or_insert_with(|| unescape_mountinfo(mountpoint));
+        }
+    }
+    mounts
+}
+
+/// Decodes octal escapes (e.g. `\040` for space) used in `mountinfo`
+fn unescape_mountinfo(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let escape = bytes.get(i + 1..i + 4).and_then(|octal| {
+            let octal = std::str::from_utf8(octal).ok()?;
+            u8::from_str_radix(octal, 8).ok()
+        });
+        match escape {
+            Some(byte) if bytes[i] == b'\\' => {
+                decoded.push(byte);
+                i += 4;
+            }
+            _ => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn print_csv(entries: &[Entry]) {
+    println!("{}", CSV_HEADER);
+
+    for entry in entries {
+        let topology = entry.topology();
+        let topology_field = |get: fn(&TopologyInfo) -> u64| {
+            topology.map(|t| get(t).to_string()).unwrap_or_default()
+        };
+        let partitions = entry.report.as_ref().map_or(0, |r| r.partitions().len());
+        let fields = [
+            entry.path.clone(),
+            entry.size.to_string(),
+            topology_field(TopologyInfo::logical_sector_size),
+            topology_field(TopologyInfo::physical_sector_size),
+            topology_field(TopologyInfo::minimum_io_size),
+            topology_field(TopologyInfo::optimal_io_size),
+            topology_field(TopologyInfo::alignment_offset),
+            entry.value(PartitionTag::Pttype).unwrap_or_default(),
+            partitions.to_string(),
+            entry.value(SuperblockTag::Type).unwrap_or_default(),
+            entry.value(SuperblockTag::Label).unwrap_or_default(),
+            entry.value(SuperblockTag::Uuid).unwrap_or_default(),
+            entry.mountpoint.clone().unwrap_or_default(),
+        ];
+        let fields = fields.iter().map(|f| csv_field(f)).collect::<Vec<_>>();
+        println!("{}", fields.join(","));
+    }
+}
+
+/// Quotes the field if it contains a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
+
+fn print_json(entries: &[Entry]) {
+    let entries = entries.iter().map(json_entry).collect::<Vec<_>>();
+    println!("[{}]", entries.join(","));
+}
+
+fn json_entry(entry: &Entry) -> String {
+    let topology = match entry.topology() {
+        Some(t) => format!(
+            "{{\"logical_sector_size\":{},\"physical_sector_size\":{},\"minimum_io_size\":{},\
+             \"optimal_io_size\":{},\"alignment_offset\":{}}}",
+            t.logical_sector_size(),
+            t.physical_sector_size(),
+            t.minimum_io_size(),
+            t.optimal_io_size(),
+            t.alignment_offset()
+        ),
+        None => "null".to_owned(),
+    };
+    let partitions = entry
+        .report
+        .iter()
+        .flat_map(FullReport::partitions)
+        .map(|p| {
+            format!(
+                "{{\"partno\":{},\"start\":{},\"size\":{},\"type\":{},\"uuid\":{}}}",
+                p.partno(),
+                p.start(),
+                p.size(),
+                json_option(p.typ()),
+                json_option(p.uuid())
+            )
+        })
+        .collect::<Vec<_>>();
+
+    format!(
+        "{{\"path\":{},\"size\":{},\"topology\":{},\"pt_type\":{},\"partitions\":[{}],\
+         \"fs_type\":{},\"label\":{},\"uuid\":{},\"mountpoint\":{}}}",
+        json_string(&entry.path),
+        entry.size,
+        topology,
+        json_option(entry.value(PartitionTag::Pttype).as_deref()),
+        partitions.join(","),
+        json_option(entry.value(SuperblockTag::Type).as_deref()),
+        json_option(entry.value(SuperblockTag::Label).as_deref()),
+        json_option(entry.value(SuperblockTag::Uuid).as_deref()),
+        json_option(entry.mountpoint.as_deref())
+    )
+}
+
+fn json_option(value: Option<&str>) -> String {
+    value.map(json_string).unwrap_or_else(|| "null".to_owned())
+}
+
+fn json_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
@@ -0,0 +1,86 @@
+//! Prints probing results of a device as udev properties, suitable for
+//! `IMPORT{program}="blkid-udev $devnode"` in udev rules
+//!
+//! Usage: `blkid-udev DEVICE`
+use blkid::{
+    prober::{ProbeState, Prober},
+    tag::{encode_string, safe_string, Tag},
+    BlkIdResult, PartitionsFlags, SuperblocksFlags,
+};
+use std::{env, process};
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let device = match (args.next(), args.next()) {
+        (Some(device), None) => device,
+        _ => {
+            eprintln!("usage: blkid-udev DEVICE");
+            process::exit(2);
+        }
+    };
+
+    match probe(&device) {
+        Ok(tags) => {
+            for tag in tags {
+                print_properties(&tag);
+            }
+        }
+        Err(err) => {
+            eprintln!("blkid-udev: {}: {}", device, err);
+            process::exit(1);
+        }
+    }
+}
+
+/// Probes the device with the same settings as the udev builtin
+fn probe(device: &str) -> BlkIdResult<Vec<Tag>> {
+    let prober = Prober::new_from_filename(device)?;
+    prober.set_superblocks_flags(
+        SuperblocksFlags::DEFAULT | SuperblocksFlags::USAGE | SuperblocksFlags::VERSION,
+    )?;
+    prober.enable_partitions(true)?;
+    prober.set_partitions_flags(PartitionsFlags::ENTRY_DETAILS)?;
+
+    match prober.do_safe_probe()? {
+        ProbeState::Success => Ok(prober.result()?.tags().to_vec()),
+        _ => Ok(Vec::new()),
+    }
+}
+
+/// Prints the value under the name used by udev, values which are not exported by udev are
+/// skipped
+fn print_properties(tag: &Tag) {
+    let name = tag.name();
+    let value = tag.value();
+
+    match name.as_str() {
+        "TYPE" | "USAGE" | "VERSION" | "SYSTEM_ID" | "PUBLISHER_ID" | "APPLICATION_ID"
+        | "BOOT_SYSTEM_ID" | "VOLUME_ID" | "LOGICAL_VOLUME_ID" | "VOLUME_SET_ID"
+        | "DATA_PREPARER_ID" => print_property(&format!("ID_FS_{}", name), value),
+        "UUID" | "UUID_SUB" | "LABEL" => {
+            let property = format!("ID_FS_{}", name);
+            print_property(&property, &safe_string(value).unwrap_or_default());
+            print_encoded(&property, value);
+        }
+        "PTTYPE" => print_property("ID_PART_TABLE_TYPE", value),
+        "PTUUID" => print_property("ID_PART_TABLE_UUID", value),
+        "PART_ENTRY_NAME" | "PART_ENTRY_TYPE" => print_encoded(&format!("ID_{}", name), value),
+        _ if name.starts_with("PART_ENTRY_") => print_property(&format!("ID_{}", name), value),
+        _ => {}
+    }
+}
+
+fn print_property(name: &str, value: &str) {
+    println!("{}={}", name, value);
+}
+
+/// Prints `NAME_ENC` property, except for `PART_ENTRY_*` which udev exports encoded under the
+/// plain name
+fn print_encoded(name: &str, value: &str) {
+    let encoded = encode_string(value).unwrap_or_default();
+    if name.starts_with("ID_PART_ENTRY_") {
+        print_property(name, &encoded);
+    } else {
+        print_property(&format!("{}_ENC", name), &encoded);
+    }
+}
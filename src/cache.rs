@@ -1,17 +1,101 @@
 use crate::{
     dev::{Dev, Devs, GetDevFlags},
     error::c_result,
+    fs::FsType,
     path_to_cstring,
-    tag::{Tag, TagType},
-    BlkIdResult,
+    prober::Prober,
+    read_only_mode,
+    scan::{probe_device, ScannedDevice},
+    sysfs::list_block_devices,
+    tag::{SuperblockTag, Tag, TagType},
+    BlkIdResult, READ_ONLY_CACHE_FILE,
 };
 use blkid_sys::*;
 use std::{
+    env,
     ffi::{CStr, CString},
-    path::Path,
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
     ptr,
+    sync::mpsc,
+    thread,
+    time::Duration,
 };
 
+/// Environment variable overriding the cache file path
+const CACHE_FILE_ENV: &str = "BLKID_FILE";
+/// Environment variable overriding the configuration file path
+const CONFIG_FILE_ENV: &str = "BLKID_CONF";
+const DEFAULT_CONFIG_FILE: &str = "/etc/blkid.conf";
+const DEFAULT_CACHE_FILE: &str = "/run/blkid/blkid.tab";
+
+/// Options of [`Cache::scan_removable`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RemovableScanOptions {
+    timeout: Duration,
+    skip_errors: bool,
+}
+
+impl Default for RemovableScanOptions {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(10),
+            skip_errors: true,
+        }
+    }
+}
+
+impl RemovableScanOptions {
+    /// Default options: 10 seconds timeout per device, devices which fail are skipped
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maximum time to wait for probing of one device
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// If `true` devices which cannot be probed or time out are skipped, otherwise the first
+    /// failure is returned as error
+    pub fn skip_errors(mut self, skip_errors: bool) -> Self {
+        self.skip_errors = skip_errors;
+        self
+    }
+}
+
+/// Reason why [`Cache::verify_all`] reports a device
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StaleReason {
+    /// The device does not exist anymore and was removed from the cache
+    Removed,
+    /// The device was probed again and its tags changed
+    Changed,
+    /// The device cannot be read (e.g. without permissions), the cached tags are kept
+    Unverified,
+}
+
+/// Cached device which was out of date or could not be verified
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StaleDevice {
+    path: PathBuf,
+    reason: StaleReason,
+}
+
+impl StaleDevice {
+    /// Device name as stored in the cache
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Why the device is reported
+    pub fn reason(&self) -> StaleReason {
+        self.reason
+    }
+}
+
 #[derive(Debug)]
 pub struct Cache(pub(crate) blkid_cache);
 
@@ -24,20 +108,56 @@ impl Drop for Cache {
 impl Cache {
     /// Creates and initialize cache handler by default path. Default path can be overridden by the
     /// environment variable `BLKID_FILE`
+    ///
+    /// In [`crate::read_only_mode`] the cache is kept in memory only.
     pub fn new() -> BlkIdResult<Self> {
+        if read_only_mode() {
+            return Self::new_by_path(READ_ONLY_CACHE_FILE);
+        }
+
         let mut cache: blkid_cache = ptr::null_mut();
         unsafe { c_result(blkid_get_cache(&mut cache, ptr::null())) }?;
         Ok(Self(cache))
     }
 
     /// Creates and initialize cache hadler by particular path
+    ///
+    /// In [`crate::read_only_mode`] the path is ignored and the cache is kept in memory only.
     pub fn new_by_path<P: AsRef<Path>>(path: P) -> BlkIdResult<Self> {
         let mut cache: blkid_cache = ptr::null_mut();
-        let path = path_to_cstring(path)?;
+        let path = if read_only_mode() {
+            path_to_cstring(READ_ONLY_CACHE_FILE)?
+        } else {
+            path_to_cstring(path)?
+        };
         unsafe { c_result(blkid_get_cache(&mut cache, path.as_ptr())) }?;
         Ok(Self(cache))
     }
 
+    /// Same as [`Self::new`] but resolves the cache file path explicitly the same way as
+    /// `libblkid`: the `BLKID_FILE` environment variable, `CACHE_FILE` from the configuration file
+    /// (`BLKID_CONF` or `/etc/blkid.conf`) and the default `/run/blkid/blkid.tab`.
+    ///
+    /// Returns the cache together with the selected path. Fails if the file exists but cannot be
+    /// read, a missing file is created when the cache is saved.
+    pub fn from_env() -> BlkIdResult<(Self, PathBuf)> {
+        let path = if read_only_mode() {
+            PathBuf::from(READ_ONLY_CACHE_FILE)
+        } else {
+            cache_file_from_env()
+        };
+
+        match File::open(&path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => {
+                let message = format!("cannot read cache file {}: {}", path.display(), err);
+                return Err(io::Error::new(err.kind(), message).into());
+            }
+            _ => {}
+        }
+
+        Ok((Self::new_by_path(&path)?, path))
+    }
+
     /// Probes all block devices
     pub fn probe_all(&self) -> BlkIdResult<()> {
         unsafe { c_result(blkid_probe_all(self.0)).map(|_| ()) }
@@ -63,11 +183,75 @@ impl Cache {
         unsafe { c_result(blkid_probe_all_removable(self.0)).map(|_| ()) }
     }
 
+    /// Safer alternative of [`Self::probe_all_removable`]. Every removable device (according to
+    /// `/sys`) is probed in a separate thread which is abandoned when it does not finish within
+    /// the timeout, so a stuck drive does not block the caller.
+    ///
+    /// This does not call [`Self::probe_all_removable`] and does not modify the cache: `libblkid`
+    /// has no way to store values probed elsewhere in a cache entry, and verifying the entry would
+    /// probe the device again on the caller's thread without the timeout. The cache lookups
+    /// (e.g. [`Self::find_dev_with_tag`]) therefore do not see the devices, use the returned
+    /// devices instead (e.g. as a [`crate::scan::DeviceSource`]).
+    ///
+    /// Returns probed devices.
+    pub fn scan_removable(
+        &self,
+        options: &RemovableScanOptions,
+    ) -> BlkIdResult<Vec<ScannedDevice>> {
+        let mut devices = Vec::new();
+
+        for info in list_block_devices()? {
+            if !info.is_removable() {
+                continue;
+            }
+
+            match probe_with_timeout(info.path().to_path_buf(), options.timeout) {
+                Ok(device) => devices.push(device),
+                Err(_) if options.skip_errors => continue,
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(devices)
+    }
+
+    /// Probes only devices whose paths match any of the shell wildcard `patterns` (e.g.
+    /// `["/dev/sd*", "/dev/nvme*"]`) and returns them. Candidates are listed from `/sys`, so
+    /// devices which do not match are never opened. Devices which cannot be probed are skipped.
+    pub fn probe_matching<S: AsRef<str>>(&self, patterns: &[S]) -> BlkIdResult<Vec<Dev>> {
+        let patterns = patterns
+            .iter()
+            .map(|pattern| CString::new(pattern.as_ref()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut devs = Vec::new();
+
+        for info in list_block_devices()? {
+            let path = path_to_cstring(info.path())?;
+            if !matches_any(&patterns, &path) {
+                continue;
+            }
+            if let Ok(dev) = self.get_dev(&info.path().to_string_lossy(), GetDevFlags::NORMAL) {
+                devs.push(dev);
+            }
+        }
+
+        Ok(devs)
+    }
+
     /// Returns iterator over all devices are found by probe
-    pub fn devs(&self) -> Devs {
+    pub fn devs(&self) -> BlkIdResult<Devs> {
         Devs::new(self)
     }
 
+    /// Returns paths and tags of all cached devices with filesystem (or other superblock) type
+    /// `fs_type`. Call [`Self::probe_all`] before to get up-to-date results.
+    pub fn devices_with_type(&self, fs_type: FsType) -> BlkIdResult<Vec<(PathBuf, Vec<Tag>)>> {
+        self.devs()?
+            .search(SuperblockTag::Type, fs_type.as_str())?
+            .map(|dev| Ok((dev.name().to_path_buf(), dev.tags()?.collect())))
+            .collect()
+    }
+
     /// Find a dev struct in the cache by device name, if available.
     ///
     /// If there is no entry with the specified device name, and the [`GetDevFlag::CREATE`] is set,
@@ -78,13 +262,36 @@ impl Cache {
         Ok(Dev(dev))
     }
 
-    /// Returns a device which matches a particular [`Tag`].
+    /// Re-probes a single device and updates its cache entry, e.g. from a hotplug handler which
+    /// knows which device changed. A device which is not cached yet is added.
+    ///
+    /// Returns `None` if the device does not exist (anymore) or cannot be probed, the stale cache
+    /// entry is removed in such case. Note that `libblkid` does not probe a device verified less
+    /// than 2 seconds ago again unless its modification time changed.
+    pub fn refresh_device<P: AsRef<Path>>(&self, path: P) -> BlkIdResult<Option<Dev>> {
+        let devname = path_to_cstring(path)?;
+        let flags = GetDevFlags::NORMAL.bits();
+        let dev = unsafe { blkid_get_dev(self.0, devname.as_ptr(), flags) };
+
+        if dev.is_null() {
+            Ok(None)
+        } else {
+            Ok(Some(Dev(dev)))
+        }
+    }
+
+    /// Returns a device which has tag `tag_type` with `value`, e.g.
+    /// `cache.find_dev_with_tag(SuperblockTag::Uuid, "...")`.
     ///
     /// If there is more than one device that matches the search specification, it returns the one
     /// with the highest priority value. This allows us to give preference to `EVMS` or `LVM` devices
-    pub fn find_dev_with_tag(&self, tag: Tag) -> BlkIdResult<Option<Dev>> {
-        let name = CString::new(tag.name())?;
-        let value = CString::new(tag.value())?;
+    pub fn find_dev_with_tag(
+        &self,
+        tag_type: impl Into<TagType>,
+        value: &str,
+    ) -> BlkIdResult<Option<Dev>> {
+        let name = CString::new(tag_type.into().to_string())?;
+        let value = CString::new(value)?;
         let dev = unsafe { blkid_find_dev_with_tag(self.0, name.as_ptr(), value.as_ptr()) };
 
         if dev.is_null() {
@@ -94,6 +301,42 @@ impl Cache {
         }
     }
 
+    /// Returns a device which matches a particular [`Tag`], see [`Self::find_dev_with_tag`]
+    pub fn find_dev_by_tag(&self, tag: &Tag) -> BlkIdResult<Option<Dev>> {
+        self.find_dev_with_tag(tag.typ(), tag.value())
+    }
+
+    /// Verifies every cached device (see [`Dev::verify`]) and returns the devices which were
+    /// removed, changed or cannot be verified. Devices verified recently are not probed again, so
+    /// the pass is cheap enough for periodic checks of long-running daemons.
+    pub fn verify_all(&self) -> BlkIdResult<Vec<StaleDevice>> {
+        let mut stale = Vec::new();
+
+        for dev in self.devs()? {
+            let path = dev.name().to_path_buf();
+            let tags = dev.tags()?.collect::<Vec<_>>();
+            // The iterator already points to the next device, so the current one may be freed
+            let verified = unsafe { blkid_verify(self.0, dev.0) };
+
+            let reason = if verified.is_null() {
+                Some(StaleReason::Removed)
+            } else if !is_readable(&path) {
+                Some(StaleReason::Unverified)
+            } else {
+                let new_tags = Dev(verified).tags()?.collect::<Vec<_>>();
+                let unchanged =
+                    new_tags.len() == tags.len() && new_tags.iter().all(|tag| tags.contains(tag));
+                (!unchanged).then_some(StaleReason::Changed)
+            };
+
+            if let Some(reason) = reason {
+                stale.push(StaleDevice { path, reason });
+            }
+        }
+
+        Ok(stale)
+    }
+
     /// Find a tag name (e.g. [`TagType::Label`] or [`TagType::Uuid`]) on a specific device
     pub fn find_tag_value(&self, tag_type: TagType, dev_name: &str) -> BlkIdResult<Option<String>> {
         let tagname = CString::new(tag_type.to_string())?;
@@ -113,3 +356,57 @@ impl Cache {
         unsafe { blkid_gc_cache(self.0) }
     }
 }
+
+/// `libblkid` keeps the cached tags of devices it cannot open
+fn is_readable(path: &Path) -> bool {
+    match File::open(path) {
+        Err(err) => !matches!(err.raw_os_error(), Some(libc::EACCES | libc::EPERM)),
+        Ok(_) => true,
+    }
+}
+
+/// Resolves the cache file path from the environment and the configuration file
+fn cache_file_from_env() -> PathBuf {
+    if let Some(path) = env::var_os(CACHE_FILE_ENV).filter(|path| !path.is_empty()) {
+        return PathBuf::from(path);
+    }
+
+    let config = env::var_os(CONFIG_FILE_ENV).unwrap_or_else(|| DEFAULT_CONFIG_FILE.into());
+    fs::read_to_string(config)
+        .ok()
+        .and_then(|content| {
+            content.lines().find_map(|line| {
+                let path = line.trim().strip_prefix("CACHE_FILE=")?.trim();
+                (!path.is_empty()).then(|| PathBuf::from(path))
+            })
+        })
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_CACHE_FILE))
+}
+
+/// Shell wildcard matching, `*` and `?` do not match `/`
+fn matches_any(patterns: &[CString], path: &CStr) -> bool {
+    patterns.iter().any(|pattern| unsafe {
+        libc::fnmatch(pattern.as_ptr(), path.as_ptr(), libc::FNM_PATHNAME) == 0
+    })
+}
+
+/// Probes the device in a new thread. The thread is detached on timeout, because a blocking read
+/// cannot be interrupted.
+fn probe_with_timeout(path: PathBuf, timeout: Duration) -> BlkIdResult<ScannedDevice> {
+    let (sender, receiver) = mpsc::channel();
+    let device_path = path.clone();
+    thread::spawn(move || {
+        let device = Prober::new_from_filename(&device_path)
+            .and_then(|prober| probe_device(&prober, &device_path));
+        // The receiver is gone if the probing timed out
+        let _ = sender.send(device);
+    });
+
+    receiver.recv_timeout(timeout).unwrap_or_else(|_| {
+        Err(io::Error::new(
+            io::ErrorKind::TimedOut,
+            format!("probing of {} timed out", path.display()),
+        )
+        .into())
+    })
+}
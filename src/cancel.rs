@@ -0,0 +1,94 @@
+//! Cooperative cancellation of long running probing
+use crate::{BlkIdError, BlkIdResult};
+use std::{
+    io,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{Receiver, RecvTimeoutError},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// How often a blocked wait checks for [`CancellationToken::cancel`]
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Token which stops probing loops and scans when cancelled or when its deadline passes.
+///
+/// Clones share the cancellation flag, so the token can be cancelled from another thread. Note
+/// that a read which hangs inside `libblkid` cannot be interrupted; the APIs which honor the token
+/// either check it between probing steps or run the probing in a separate thread and abandon it.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+impl CancellationToken {
+    /// Token without deadline, cancelled by [`Self::cancel`] only
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Token which is cancelled at `deadline`
+    pub fn with_deadline(deadline: Instant) -> Self {
+        Self {
+            cancelled: Arc::default(),
+            deadline: Some(deadline),
+        }
+    }
+
+    /// Token which is cancelled after `timeout` from now
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self::with_deadline(Instant::now() + timeout)
+    }
+
+    /// Cancels the token and all its clones
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Deadline of the token
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Returns `true` if the token was cancelled or the deadline passed
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+            || self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// Returns [`BlkIdError::Cancelled`] if the token is cancelled
+    pub fn check(&self) -> BlkIdResult<()> {
+        if self.is_cancelled() {
+            Err(BlkIdError::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Waits for the result of a probing thread until the token is cancelled
+    pub(crate) fn wait<T>(&self, receiver: &Receiver<BlkIdResult<T>>) -> BlkIdResult<T> {
+        loop {
+            self.check()?;
+            let timeout = match self.deadline {
+                Some(deadline) => {
+                    POLL_INTERVAL.min(deadline.saturating_duration_since(Instant::now()))
+                }
+                None => POLL_INTERVAL,
+            };
+
+            match receiver.recv_timeout(timeout) {
+                Ok(result) => return result,
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(RecvTimeoutError::Disconnected) => {
+                    let err = io::Error::other("probing thread panicked");
+                    return Err(err.into());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,105 @@
+//! One-call classification of device content
+use crate::{
+    fs::FsType,
+    part_table::PartitionTableType,
+    probe_result::ProbeResult,
+    prober::{ProbeState, Prober},
+    tag::{PartitionTag, SuperblockTag},
+    BlkIdResult, SuperblocksFlags,
+};
+use std::path::Path;
+
+/// What is stored on a device according to the superblocks and partitions chains
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ContentKind {
+    /// No known signature was detected
+    Empty,
+    /// Filesystem (`USAGE=filesystem`)
+    Filesystem(FsType),
+    /// Partition table without any whole-device superblock
+    PartitionTable(PartitionTableType),
+    /// Member of a RAID, LVM volume group or multi-device pool (`USAGE=raid`)
+    RaidMember(FsType),
+    /// Encrypted container (`USAGE=crypto`)
+    Crypto(FsType),
+    /// Anything else with a known superblock, e.g. swap area (`USAGE=other`)
+    Other(FsType),
+    /// ISO9660 (or UDF) image with a partition table, so that it boots from optical media as well
+    /// as from a disk (e.g. USB stick). Both signatures are legitimate and none is a leftover
+    IsoHybrid {
+        fs: FsType,
+        tables: Vec<PartitionTableType>,
+    },
+    /// More than one signature was detected and `libblkid` refuses to pick one. Contains the
+    /// types of all detected signatures
+    Ambivalent(Vec<String>),
+}
+
+/// Probes the device (or image file) and condenses the superblocks and partitions chains
+/// results into a single answer
+pub fn classify<P: AsRef<Path>>(path: P) -> BlkIdResult<ContentKind> {
+    let prober = Prober::new_from_filename(path)?;
+    prober.set_superblocks_flags(SuperblocksFlags::DEFAULT | SuperblocksFlags::USAGE)?;
+    prober.enable_partitions(true)?;
+
+    match prober.do_safe_probe()? {
+        ProbeState::Ambivalent => {
+            let results = prober.probe_all_signatures()?;
+            Ok(iso_hybrid(&results).unwrap_or_else(|| {
+                ContentKind::Ambivalent(results.iter().filter_map(signature_type).collect())
+            }))
+        }
+        ProbeState::Success => {
+            let result = prober.result()?;
+
+            if let Some(kind) = iso_hybrid(std::slice::from_ref(&result)) {
+                Ok(kind)
+            } else if let Some(fs_type) = result.fs_type() {
+                let kind = match result.usage() {
+                    Some("filesystem") => ContentKind::Filesystem(fs_type),
+                    Some("raid") => ContentKind::RaidMember(fs_type),
+                    Some("crypto") => ContentKind::Crypto(fs_type),
+                    _ => ContentKind::Other(fs_type),
+                };
+                Ok(kind)
+            } else if let Some(pt_type) = result.pt_type() {
+                Ok(ContentKind::PartitionTable(pt_type))
+            } else {
+                Ok(ContentKind::Empty)
+            }
+        }
+        _ => Ok(ContentKind::Empty),
+    }
+}
+
+fn signature_type(result: &ProbeResult) -> Option<String> {
+    result
+        .get(SuperblockTag::Type)
+        .or_else(|| result.get(PartitionTag::Pttype))
+        .map(str::to_owned)
+}
+
+/// Recognizes an optical media filesystem accompanied by partition tables only. Any other
+/// signature (e.g. a second filesystem) keeps the results ambivalent
+fn iso_hybrid(results: &[ProbeResult]) -> Option<ContentKind> {
+    let mut fs = None;
+    let mut tables = Vec::new();
+
+    for result in results {
+        match result.fs_type() {
+            Some(fs_type @ (FsType::Iso9660 | FsType::Udf)) if fs.is_none() => fs = Some(fs_type),
+            Some(_) => return None,
+            None => {}
+        }
+        if let Some(pt_type) = result.pt_type() {
+            if !tables.contains(&pt_type) {
+                tables.push(pt_type);
+            }
+        }
+    }
+
+    match fs {
+        Some(fs) if !tables.is_empty() => Some(ContentKind::IsoHybrid { fs, tables }),
+        _ => None,
+    }
+}
@@ -1,8 +1,12 @@
-use crate::{cache::Cache, tag::Tags};
+use crate::{
+    cache::Cache,
+    error::{c_result, BlkIdResult},
+    tag::{TagType, Tags},
+};
 use bitflags::bitflags;
 use blkid_sys::*;
 use std::{
-    ffi::{CStr, OsStr},
+    ffi::{CStr, CString, OsStr},
     os::unix::ffi::OsStrExt,
     path::Path,
     ptr,
@@ -35,10 +39,23 @@ impl Iterator for Devs {
 
 impl Devs {
     /// Creates wrapper around device
-    pub fn new(cache: &Cache) -> Devs {
-        let iter = unsafe { blkid_dev_iterate_begin(cache.0) };
-        assert_ne!(iter, ptr::null_mut());
-        Devs { iter }
+    pub fn new(cache: &Cache) -> BlkIdResult<Devs> {
+        let iter = unsafe { c_result(blkid_dev_iterate_begin(cache.0)) }?;
+        Ok(Devs { iter })
+    }
+
+    /// Limits the iteration to devices which have tag `tag_type` with `value`
+    pub fn search(self, tag_type: impl Into<TagType>, value: &str) -> BlkIdResult<Devs> {
+        let name = CString::new(tag_type.into().to_string())?;
+        let value = CString::new(value)?;
+        unsafe {
+            c_result(blkid_dev_set_search(
+                self.iter,
+                name.as_ptr(),
+                value.as_ptr(),
+            ))
+        }?;
+        Ok(self)
     }
 }
 
@@ -73,7 +90,7 @@ impl Dev {
     }
 
     /// Returns device's tags
-    pub fn tags(&self) -> Tags {
+    pub fn tags(&self) -> BlkIdResult<Tags> {
         Tags::new(self)
     }
 }
@@ -90,3 +107,10 @@ bitflags! {
         const NORMAL = Self::CREATE.bits | Self::VERIFY.bits;
     }
 }
+
+flag_names!(GetDevFlags {
+    FIND,
+    CREATE,
+    VERIFY,
+    NORMAL,
+});
@@ -0,0 +1,110 @@
+//! High-level view of a probed block device or image file
+use crate::{
+    fs::FsType,
+    part_table::PartTableInfo,
+    probe_result::ProbeResult,
+    prober::Prober,
+    report::{FullReport, PartitionInfo, TopologyInfo},
+    scan, BlkIdResult, OpenFlags,
+};
+use std::path::{Path, PathBuf};
+
+/// Block device (or image file) probed by the superblocks and partitions chains, together with
+/// its partitions and topology. It covers the common cases without stitching [`Prober`],
+/// lookups of the values and [`crate::part_list::PartList`] together.
+///
+/// ```no_run
+/// use blkid::device::Device;
+///
+/// let device = Device::open("/dev/sda")?;
+/// println!("{:?} {:?}", device.fs_type(), device.uuid());
+/// for partition in device.partitions() {
+///     println!("{} {}", partition.partno(), partition.size());
+/// }
+/// # Ok::<(), blkid::BlkIdError>(())
+/// ```
+pub struct Device {
+    path: PathBuf,
+    prober: Prober,
+    size: u64,
+    report: FullReport,
+}
+
+impl Device {
+    /// Opens the device read-only and runs the safe probing, see [`Self::open_with`]
+    pub fn open<P: AsRef<Path>>(path: P) -> BlkIdResult<Self> {
+        Self::open_with(path, OpenFlags::empty())
+    }
+
+    /// Opens the device with `flags` and runs the safe probing of the superblocks (including
+    /// `USAGE`) and partitions (including entry details) chains
+    pub fn open_with<P: AsRef<Path>>(path: P, flags: OpenFlags) -> BlkIdResult<Self> {
+        let path = path.as_ref();
+        let prober = Prober::open(path, flags)?;
+        scan::configure(&prober)?;
+        let report = prober.full_report().map_err(|err| err.with_path(path))?;
+        let size = prober.get_size()? as u64;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            prober,
+            size,
+            report,
+        })
+    }
+
+    /// Path the device was opened from
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Size of the device in bytes
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// All `NAME=value` results, empty if nothing was detected or the result is ambivalent
+    pub fn result(&self) -> &ProbeResult {
+        self.report.values()
+    }
+
+    /// Filesystem (or other superblock) type
+    pub fn fs_type(&self) -> Option<FsType> {
+        self.result().fs_type()
+    }
+
+    /// Filesystem label
+    pub fn label(&self) -> Option<&str> {
+        self.result().label()
+    }
+
+    /// Filesystem UUID
+    pub fn uuid(&self) -> Option<&str> {
+        self.result().uuid()
+    }
+
+    /// Partition table, `None` if there is no partition table
+    pub fn part_table(&self) -> Option<&PartTableInfo> {
+        self.report.table()
+    }
+
+    /// Partitions of the partition table
+    pub fn partitions(&self) -> &[PartitionInfo] {
+        self.report.partitions()
+    }
+
+    /// Topology, `None` if it is not available (e.g. for regular files)
+    pub fn topology(&self) -> Option<&TopologyInfo> {
+        self.report.topology()
+    }
+
+    /// All results of the probing
+    pub fn report(&self) -> &FullReport {
+        &self.report
+    }
+
+    /// The prober assigned to the device, for the low-level interfaces
+    pub fn prober(&self) -> &Prober {
+        &self.prober
+    }
+}
@@ -0,0 +1,115 @@
+use crate::BlkIdError;
+use blkid_sys::blkid_devno_to_devname;
+use std::{
+    ffi::{CStr, OsStr},
+    fmt,
+    os::unix::ffi::OsStrExt,
+    path::PathBuf,
+    str::FromStr,
+};
+
+/// Device number (`dev_t`) of a block device
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Devno(u64);
+
+impl Devno {
+    /// Creates device number from `major` and `minor` numbers
+    pub fn new(major: u32, minor: u32) -> Self {
+        Self::from_dev_t(libc::makedev(major, minor))
+    }
+
+    /// `dev_t` is 32 bits wide on some 32-bit targets, the crate keeps 64 bits everywhere
+    #[allow(clippy::unnecessary_cast)]
+    pub(crate) fn from_dev_t(devno: libc::dev_t) -> Self {
+        Self(devno as u64)
+    }
+
+    /// The value is truncated on targets with 32-bit `dev_t`, such device numbers cannot exist
+    /// there anyway
+    #[allow(clippy::unnecessary_cast)]
+    pub(crate) fn as_dev_t(&self) -> libc::dev_t {
+        self.0 as libc::dev_t
+    }
+
+    /// Returns major number of the device
+    pub fn major(&self) -> u32 {
+        libc::major(self.as_dev_t())
+    }
+
+    /// Returns minor number of the device
+    pub fn minor(&self) -> u32 {
+        libc::minor(self.as_dev_t())
+    }
+
+    /// Returns raw `dev_t` value
+    pub fn as_raw(&self) -> u64 {
+        self.0
+    }
+
+    /// Resolves the device node (e.g. `/dev/sda`) by scanning `/dev`, `None` if no node is found
+    pub fn to_devname(&self) -> Option<PathBuf> {
+        let name = unsafe { blkid_devno_to_devname(self.as_dev_t()) };
+        if name.is_null() {
+            return None;
+        }
+
+        let path = PathBuf::from(OsStr::from_bytes(
+            unsafe { CStr::from_ptr(name) }.to_bytes(),
+        ));
+        unsafe { libc::free(name as *mut libc::c_void) };
+        Some(path)
+    }
+}
+
+impl From<u64> for Devno {
+    fn from(devno: u64) -> Self {
+        Self(devno)
+    }
+}
+
+impl From<Devno> for u64 {
+    fn from(devno: Devno) -> Self {
+        devno.0
+    }
+}
+
+impl fmt::Display for Devno {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.major(), self.minor())
+    }
+}
+
+/// Parses `MAJ:MIN` notation (e.g. content of `/sys/block/sda/dev` file)
+impl FromStr for Devno {
+    type Err = BlkIdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (major, minor) = s
+            .trim()
+            .split_once(':')
+            .ok_or_else(|| BlkIdError::InvalidDevno(s.to_owned()))?;
+        let major = major
+            .parse()
+            .map_err(|_| BlkIdError::InvalidDevno(s.to_owned()))?;
+        let minor = minor
+            .parse()
+            .map_err(|_| BlkIdError::InvalidDevno(s.to_owned()))?;
+        Ok(Self::new(major, minor))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn large_numbers_round_trip() {
+        // Minor numbers above 255 and major numbers above 4095 use the upper 32 bits of dev_t
+        let devno: Devno = "4096:1048575".parse().unwrap();
+        assert_eq!(devno.major(), 4096);
+        assert_eq!(devno.minor(), 1048575);
+        assert!(devno.as_raw() > u64::from(u32::MAX));
+        assert_eq!(Devno::from(devno.as_raw()), devno);
+        assert_eq!(devno.to_string(), "4096:1048575");
+    }
+}
@@ -0,0 +1,80 @@
+//! Helpers for device-mapper devices of the [`devicemapper`](https://docs.rs/devicemapper) crate
+use crate::{
+    devno::Devno,
+    prober::Prober,
+    scan::{probe_device, ScannedDevice},
+    system_roots, BlkIdResult, OpenFlags,
+};
+use devicemapper::{DevId, Device, DmName, DmNameBuf, DmOptions, DM};
+use std::{
+    collections::HashSet,
+    fs,
+    os::unix::fs::MetadataExt,
+    path::{Path, PathBuf},
+};
+
+impl From<Device> for Devno {
+    fn from(device: Device) -> Self {
+        Self::new(device.major, device.minor)
+    }
+}
+
+impl From<Devno> for Device {
+    fn from(devno: Devno) -> Self {
+        Self {
+            major: devno.major(),
+            minor: devno.minor(),
+        }
+    }
+}
+
+/// Path to the device node of the DM device (`/dev/mapper/<name>`)
+pub fn dm_path(name: &DmName) -> PathBuf {
+    system_roots()
+        .get_dev()
+        .join("mapper")
+        .join(name.to_string())
+}
+
+/// Name of the DM device with the device number, `None` if it is not a DM device
+pub fn dm_name(dm: &DM, devno: Devno) -> BlkIdResult<Option<DmNameBuf>> {
+    let device = Device::from(devno);
+    Ok(dm
+        .list_devices()?
+        .into_iter()
+        .find(|(_, dev, _)| *dev == device)
+        .map(|(name, _, _)| name))
+}
+
+/// Name of the DM device of the device node (e.g. `/dev/dm-0`), `None` if it is not a DM device
+pub fn dm_name_of_path<P: AsRef<Path>>(dm: &DM, path: P) -> BlkIdResult<Option<DmNameBuf>> {
+    let devno = Devno::from(fs::metadata(path)?.rdev());
+    dm_name(dm, devno)
+}
+
+/// Probes the DM device `name` and all DM devices below it in the table tree (e.g. a dm-crypt
+/// device under an LVM logical volume), top-down. Devices which are not DM devices (disks,
+/// partitions) are not probed, see [`crate::sysfs::DeviceInfo::slaves_tree`] for the whole stack.
+pub fn probe_tree(dm: &DM, name: &DmName) -> BlkIdResult<Vec<ScannedDevice>> {
+    let devices = dm.list_devices()?;
+    let mut visited = HashSet::new();
+    let mut pending = vec![name.to_owned()];
+    let mut results = Vec::new();
+
+    while let Some(name) = pending.pop() {
+        if !visited.insert(name.clone()) {
+            continue;
+        }
+
+        let path = dm_path(&name);
+        let prober = Prober::open(&path, OpenFlags::empty())?;
+        results.push(probe_device(&prober, &path)?);
+
+        for dep in dm.table_deps(DevId::Name(&name), DmOptions::default())? {
+            let deps = devices.iter().filter(|(_, device, _)| *device == dep);
+            pending.extend(deps.map(|(name, _, _)| name.clone()));
+        }
+    }
+
+    Ok(results)
+}
@@ -0,0 +1,170 @@
+//! Roles of GPT partitions according to the Discoverable Partitions Specification (DPS)
+//!
+//! See <https://uapi-group.org/specifications/specs/discoverable_partitions_specification/>
+use crate::{gpt, part_table::PartitionTableType, partition::Partition};
+
+/// CPU architecture of root and `/usr` partitions
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DpsArch {
+    X86,
+    X86_64,
+    Arm,
+    Arm64,
+    Ia64,
+    LoongArch64,
+    RiscV32,
+    RiscV64,
+    Ppc,
+    Ppc64,
+    Ppc64Le,
+    S390,
+    S390x,
+}
+
+impl DpsArch {
+    /// Architecture the crate is compiled for, `None` if the specification does not cover it
+    pub fn current() -> Option<Self> {
+        if cfg!(target_arch = "x86") {
+            Some(Self::X86)
+        } else if cfg!(target_arch = "x86_64") {
+            Some(Self::X86_64)
+        } else if cfg!(target_arch = "arm") {
+            Some(Self::Arm)
+        } else if cfg!(target_arch = "aarch64") {
+            Some(Self::Arm64)
+        } else if cfg!(target_arch = "loongarch64") {
+            Some(Self::LoongArch64)
+        } else if cfg!(target_arch = "riscv32") {
+            Some(Self::RiscV32)
+        } else if cfg!(target_arch = "riscv64") {
+            Some(Self::RiscV64)
+        } else if cfg!(target_arch = "powerpc") {
+            Some(Self::Ppc)
+        } else if cfg!(all(target_arch = "powerpc64", target_endian = "big")) {
+            Some(Self::Ppc64)
+        } else if cfg!(all(target_arch = "powerpc64", target_endian = "little")) {
+            Some(Self::Ppc64Le)
+        } else if cfg!(target_arch = "s390x") {
+            Some(Self::S390x)
+        } else {
+            None
+        }
+    }
+}
+
+/// Role of a partition defined by its GPT type GUID
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DpsRole {
+    /// Root filesystem (`/`) for the architecture
+    Root(DpsArch),
+    /// `/usr` filesystem for the architecture
+    Usr(DpsArch),
+    /// `/home`
+    Home,
+    /// `/srv`
+    Srv,
+    /// `/var`
+    Var,
+    /// `/var/tmp`
+    Tmp,
+    /// Swap area
+    Swap,
+    /// EFI System Partition, mounted to `/efi` or `/boot`
+    Esp,
+    /// Extended Boot Loader Partition, mounted to `/boot`
+    Xbootldr,
+}
+
+/// GPT partition type GUIDs of the roles
+#[rustfmt::skip]
+const ROLES: &[(DpsRole, &str)] = &[
+    (DpsRole::Root(DpsArch::X86), "44479540-f297-41b2-9af7-d131d5f0458a"),
+    (DpsRole::Root(DpsArch::X86_64), "4f68bce3-e8cd-4db1-96e7-fbcaf984b709"),
+    (DpsRole::Root(DpsArch::Arm), "69dad710-2ce4-4e3c-b16c-21a1d49abed3"),
+    (DpsRole::Root(DpsArch::Arm64), "b921b045-1df0-41c3-af44-4c6f280d3fae"),
+    (DpsRole::Root(DpsArch::Ia64), "993d8d3d-f80e-4225-855a-9daf8ed7ea97"),
+    (DpsRole::Root(DpsArch::LoongArch64), "77055800-792c-4f94-b39a-98c91b762bb6"),
+    (DpsRole::Root(DpsArch::RiscV32), "60d5a7fe-8e7d-435c-b714-3dd8162144e1"),
+    (DpsRole::Root(DpsArch::RiscV64), "72ec70a6-cf74-40e6-bd49-4bda08e8f224"),
+    (DpsRole::Root(DpsArch::Ppc), "1de3f1ef-fa98-47b5-8dcd-4a860a654d78"),
+    (DpsRole::Root(DpsArch::Ppc64), "912ade1d-a839-4913-8964-a10eee08fbd2"),
+    (DpsRole::Root(DpsArch::Ppc64Le), "c31c45e6-3f39-412e-80fb-4809c4980599"),
+    (DpsRole::Root(DpsArch::S390), "08a7acea-624c-4a20-91e8-6e0fa67d23f9"),
+    (DpsRole::Root(DpsArch::S390x), "5eead9a9-fe09-4a1e-a1d7-520d00531306"),
+    (DpsRole::Usr(DpsArch::X86), "75250d76-8cc6-458e-bd66-bd47cc81a812"),
+    (DpsRole::Usr(DpsArch::X86_64), "8484680c-9521-48c6-9c11-b0720656f69e"),
+    (DpsRole::Usr(DpsArch::Arm), "7d0359a3-02b3-4f0a-865c-654403e70625"),
+    (DpsRole::Usr(DpsArch::Arm64), "b0e01050-ee5f-4390-949a-9101b17104e9"),
+    (DpsRole::Usr(DpsArch::Ia64), "4301d2a6-4e3b-4b2a-bb94-9e0b2c4225ea"),
+    (DpsRole::Usr(DpsArch::LoongArch64), "e611c702-575c-4cbe-9a46-434fa0bf7e3f"),
+    (DpsRole::Usr(DpsArch::RiscV32), "b933fb22-5c3f-4f91-af90-e2bb0fa50702"),
+    (DpsRole::Usr(DpsArch::RiscV64), "beaec34b-8442-439b-a40b-984381ed097d"),
+    (DpsRole::Usr(DpsArch::Ppc), "7d14fec5-cc71-415d-9d6c-06bf0b3c3eaf"),
+    (DpsRole::Usr(DpsArch::Ppc64), "2c9739e2-f068-46b3-9fd0-01c5a9afbcca"),
+    (DpsRole::Usr(DpsArch::Ppc64Le), "15bb03af-77e7-4d4a-b12b-c0d084f7491c"),
+    (DpsRole::Usr(DpsArch::S390), "cd0f869b-d0fb-4ca0-b141-9ea87cc78d66"),
+    (DpsRole::Usr(DpsArch::S390x), "8a4f5770-50aa-4ed3-874a-99b710db6fea"),
+    (DpsRole::Home, "933ac7e1-2eb4-4f13-b844-0e14e2aef915"),
+    (DpsRole::Srv, "3b8f8425-20e0-4f3b-907f-1a25a76f98e8"),
+    (DpsRole::Var, "4d21b016-b534-45c2-a9fb-5c16e091fd2d"),
+    (DpsRole::Tmp, "7ec6f557-3bc5-4aca-b293-16ef5df639d1"),
+    (DpsRole::Swap, gpt::LINUX_SWAP_TYPE),
+    (DpsRole::Esp, gpt::EFI_SYSTEM_TYPE),
+    (DpsRole::Xbootldr, "bc13c2ff-59e6-4262-a352-b275fd6f7172"),
+];
+
+impl DpsRole {
+    /// Role of the GPT partition type GUID (case insensitive), `None` for types not covered by
+    /// the specification
+    pub fn from_type_guid(type_guid: &str) -> Option<Self> {
+        ROLES
+            .iter()
+            .find(|(_, guid)| guid.eq_ignore_ascii_case(type_guid))
+            .map(|(role, _)| *role)
+    }
+
+    /// GPT partition type GUID of the role
+    pub fn type_guid(&self) -> &'static str {
+        ROLES
+            .iter()
+            .find(|(role, _)| role == self)
+            .map(|(_, guid)| *guid)
+            .unwrap_or_default()
+    }
+
+    /// Where the partition is mounted, `None` for swap. The ESP is reported as `/efi`, systems
+    /// without XBOOTLDR mount it to `/boot` instead
+    pub fn mount_point(&self) -> Option<&'static str> {
+        match self {
+            Self::Root(_) => Some("/"),
+            Self::Usr(_) => Some("/usr"),
+            Self::Home => Some("/home"),
+            Self::Srv => Some("/srv"),
+            Self::Var => Some("/var"),
+            Self::Tmp => Some("/var/tmp"),
+            Self::Swap => None,
+            Self::Esp => Some("/efi"),
+            Self::Xbootldr => Some("/boot"),
+        }
+    }
+
+    /// Architecture of root and `/usr` partitions, `None` for other roles
+    pub fn arch(&self) -> Option<DpsArch> {
+        match self {
+            Self::Root(arch) | Self::Usr(arch) => Some(*arch),
+            _ => None,
+        }
+    }
+}
+
+impl Partition<'_> {
+    /// Discoverable Partitions Specification role of the partition, `None` for partitions of
+    /// other than GPT tables and for types not covered by the specification
+    pub fn dps_role(&self) -> Option<DpsRole> {
+        let table = self.table().ok()?;
+        if table.get_type() != Some(PartitionTableType::Gpt) {
+            return None;
+        }
+        DpsRole::from_type_guid(&self.typ_string()?)
+    }
+}
@@ -1,4 +1,10 @@
-use std::{ffi::NulError, io, str::Utf8Error};
+use crate::tag::TagType;
+use std::{
+    ffi::NulError,
+    io,
+    path::{Path, PathBuf},
+    str::Utf8Error,
+};
 use thiserror::Error;
 
 pub type BlkIdResult<T, E = BlkIdError> = std::result::Result<T, E>;
@@ -13,6 +19,109 @@ pub enum BlkIdError {
 
     #[error(transparent)]
     Io(#[from] io::Error),
+
+    #[error("invalid device number: {0:?}")]
+    InvalidDevno(String),
+
+    #[error("invalid tag, expected NAME=value: {0:?}")]
+    InvalidTag(String),
+
+    #[error("invalid version string: {0:?}")]
+    InvalidVersion(String),
+
+    #[error("invalid flag name: {0:?}")]
+    InvalidFlag(String),
+
+    #[error("unknown name: {0:?}")]
+    UnknownName(String),
+
+    #[error("input is not seekable (e.g. pipe or socket), libblkid needs random access")]
+    NotSeekable,
+
+    #[error("input is empty, there is nothing to probe")]
+    EmptyInput,
+
+    #[error("probing was cancelled")]
+    Cancelled,
+
+    #[error(
+        "missing required values: {}",
+        .0.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+    )]
+    MissingTags(Vec<TagType>),
+
+    #[error("refusing to write, the read-only mode is enabled")]
+    ReadOnlyMode,
+
+    #[error(
+        "permission denied to {}: reading block devices requires read access to the device \
+         node (run as root or add the user to the 'disk' group), some ioctls need CAP_SYS_RAWIO",
+        .path.display()
+    )]
+    PermissionDenied { path: PathBuf, source: io::Error },
+
+    #[error("no medium found in {}", .path.display())]
+    NoMedium { path: PathBuf },
+
+    #[error("invalid GUID: {0:?}")]
+    InvalidGuid(String),
+
+    #[error("partition (start {start}, size {size} sectors) is not aligned to {block_size} bytes")]
+    UnalignedPartition {
+        start: i64,
+        size: i64,
+        block_size: u64,
+    },
+
+    #[cfg(feature = "devicemapper")]
+    #[error(transparent)]
+    DeviceMapper(#[from] devicemapper::DmError),
+}
+
+impl BlkIdError {
+    /// Converts `EACCES` and `EPERM` failures of opening `path` to [`Self::PermissionDenied`] and
+    /// `ENOMEDIUM` failures (e.g. empty CD-ROM drive) to [`Self::NoMedium`]
+    pub(crate) fn with_path(self, path: &Path) -> Self {
+        match self {
+            Self::Io(err) if err.raw_os_error() == Some(libc::ENOMEDIUM) => Self::NoMedium {
+                path: path.to_path_buf(),
+            },
+            Self::Io(err) if matches!(err.raw_os_error(), Some(libc::EACCES | libc::EPERM)) => {
+                Self::PermissionDenied {
+                    path: path.to_path_buf(),
+                    source: err,
+                }
+            }
+            err => err,
+        }
+    }
+
+    /// Returns `true` if the same operation may succeed later because the device is transiently
+    /// busy (`EBUSY`, `EAGAIN`), see [`crate::retry::RetryPolicy`]
+    pub fn is_retryable(&self) -> bool {
+        matches!(self.raw_os_error(), Some(libc::EBUSY | libc::EAGAIN))
+    }
+
+    /// Returns `true` if the device (or file) does not exist (`ENOENT`, `ENODEV`, `ENXIO`)
+    pub fn is_not_found(&self) -> bool {
+        matches!(
+            self.raw_os_error(),
+            Some(libc::ENOENT | libc::ENODEV | libc::ENXIO)
+        )
+    }
+
+    /// Returns `true` if the access to the device was denied, see [`Self::PermissionDenied`]
+    pub fn is_permission(&self) -> bool {
+        matches!(self, Self::PermissionDenied { .. })
+            || matches!(self.raw_os_error(), Some(libc::EACCES | libc::EPERM))
+    }
+
+    fn raw_os_error(&self) -> Option<i32> {
+        match self {
+            Self::Io(err) | Self::PermissionDenied { source: err, .. } => err.raw_os_error(),
+            _ => None,
+        }
+    }
 }
 
 pub(crate) trait RawResult: Copy {
@@ -0,0 +1,71 @@
+//! Rendering of probing results in the output formats of `blkid(8)`
+use crate::{probe_result::ProbeResult, tag::escape_value};
+use std::path::Path;
+
+/// Characters escaped by `blkid -o export`, so the output can be sourced by a shell
+const EXPORT_ESCAPED: &str = " \\\"'$`<>";
+
+/// Output format of `blkid(8)`, see its `--output` option
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// `-o value`: bare values, one per line
+    Value,
+    /// `-o export`: `DEVNAME=device` followed by `NAME=value` lines. Devices are separated by an
+    /// empty line
+    Export,
+    /// `-o full` (the default): `device: NAME="value" ...` on a single line
+    Full,
+}
+
+/// Renders `result` of `device` the same way as `blkid -p -o <format> device`, including the
+/// trailing newline. Nothing is printed for an empty result.
+pub fn render<P: AsRef<Path>>(device: P, result: &ProbeResult, format: OutputFormat) -> String {
+    let device = device.as_ref().display();
+    if result.is_empty() {
+        return String::new();
+    }
+
+    let mut output = String::new();
+    match format {
+        OutputFormat::Value => {
+            for tag in result.tags() {
+                output.push_str(tag.value());
+                output.push('\n');
+            }
+        }
+        OutputFormat::Export => {
+            output.push_str(&format!("DEVNAME={}\n", device));
+            for tag in result.tags() {
+                let value = escape_value(tag.value(), EXPORT_ESCAPED);
+                output.push_str(&format!("{}={}\n", tag.name(), value));
+            }
+        }
+        OutputFormat::Full => {
+            output.push_str(&format!("{}:", device));
+            for tag in result.tags() {
+                output.push_str(&format!(" {}", tag));
+            }
+            output.push('\n');
+        }
+    }
+    output
+}
+
+/// Renders results of several devices like `blkid -p -o <format> device...`, devices with empty
+/// result are skipped
+pub fn render_all<'a, P, I>(results: I, format: OutputFormat) -> String
+where
+    P: AsRef<Path> + 'a,
+    I: IntoIterator<Item = &'a (P, ProbeResult)>,
+{
+    let devices = results
+        .into_iter()
+        .map(|(device, result)| render(device, result, format))
+        .filter(|output| !output.is_empty())
+        .collect::<Vec<_>>();
+
+    match format {
+        OutputFormat::Export => devices.join("\n"),
+        _ => devices.concat(),
+    }
+}
@@ -0,0 +1,485 @@
+//! Filesystem (superblock) specific helpers
+use crate::{
+    probe_result::ProbeResult,
+    prober::{ProbeState, Prober},
+    scan::{DeviceSource, ScannedDevice},
+    sysfs::DeviceInfo,
+    tag::SuperblockTag,
+    BlkIdResult,
+};
+use std::{cmp::Ordering, fmt, str::FromStr};
+
+/// Label at the begin of BlueStore block devices, `libblkid` older than 2.33 does not know it
+const BLUESTORE_LABEL: &[u8] = b"bluestore block device\n";
+/// Prefix of device-mapper names of logical volumes created by `ceph-volume` (VG `ceph-<uuid>`,
+/// dashes are doubled in device-mapper names)
+const CEPH_VOLUME_DM_PREFIX: &str = "ceph--";
+
+string_enum! {
+    /// Superblock type as reported in `TYPE` value.
+    ///
+    /// Contains the most common types only, the rest is reported as [`FsType::Unknown`].
+    #[derive(Clone, Debug, Eq, PartialEq, Hash)]
+    pub enum FsType {
+        Apfs = "apfs",
+        Bcache = "bcache",
+        Bcachefs = "bcachefs",
+        Btrfs = "btrfs",
+        CephBluestore = "ceph_bluestore",
+        CryptoLuks = "crypto_LUKS",
+        Erofs = "erofs",
+        Exfat = "exfat",
+        Ext2 = "ext2",
+        Ext3 = "ext3",
+        Ext4 = "ext4",
+        Ext4dev = "ext4dev",
+        F2fs = "f2fs",
+        Gfs2 = "gfs2",
+        Hfs = "hfs",
+        Hfsplus = "hfsplus",
+        Iso9660 = "iso9660",
+        Jbd = "jbd",
+        Jfs = "jfs",
+        LinuxRaidMember = "linux_raid_member",
+        Lvm2Member = "LVM2_member",
+        Nilfs2 = "nilfs2",
+        Ntfs = "ntfs",
+        Ocfs2 = "ocfs2",
+        Reiserfs = "reiserfs",
+        Squashfs = "squashfs",
+        Swap = "swap",
+        Udf = "udf",
+        Vfat = "vfat",
+        Xfs = "xfs",
+        XfsExternalLog = "xfs_external_log",
+        ZfsMember = "zfs_member",
+        _ => Unknown,
+    }
+}
+
+/// Filesystem version as reported in `VERSION` value (e.g. "1.0" for ext4, "FAT32" or
+/// "LVM2 001").
+///
+/// Versions made of dot separated numbers (optionally prefixed by `v`) are compared numerically,
+/// missing components count as zero, so "1.0" equals "1" and is less than "2". Other versions
+/// are equal only to the same string and cannot be ordered.
+#[derive(Clone, Debug)]
+pub struct FsVersion {
+    raw: String,
+    numbers: Option<Vec<u64>>,
+}
+
+impl FsVersion {
+    /// Parses the version, never fails
+    pub fn parse(version: &str) -> Self {
+        let raw = version.trim();
+        let numeric = raw.strip_prefix(['v', 'V']).unwrap_or(raw);
+        let numbers = numeric
+            .split('.')
+            .map(|number| {
+                if number.bytes().all(|c| c.is_ascii_digit()) {
+                    number.parse().ok()
+                } else {
+                    None
+                }
+            })
+            .collect::<Option<Vec<u64>>>();
+
+        Self {
+            raw: raw.to_owned(),
+            numbers,
+        }
+    }
+
+    /// The version as reported by `libblkid`
+    pub fn as_str(&self) -> &str {
+        &self.raw
+    }
+
+    /// Numeric components, `None` if the version is not numeric
+    pub fn numbers(&self) -> Option<&[u64]> {
+        self.numbers.as_deref()
+    }
+
+    /// Returns `true` if the version is numeric and not lower than `minimum`
+    pub fn at_least(&self, minimum: &str) -> bool {
+        self.partial_cmp(&Self::parse(minimum))
+            .is_some_and(|order| order != Ordering::Less)
+    }
+
+    /// Numeric components without trailing zeros
+    fn normalized(&self) -> Option<&[u64]> {
+        let numbers = self.numbers.as_deref()?;
+        let len = numbers
+            .iter()
+            .rposition(|&n| n != 0)
+            .map_or(0, |pos| pos + 1);
+        Some(&numbers[..len])
+    }
+}
+
+impl PartialEq for FsVersion {
+    fn eq(&self, other: &Self) -> bool {
+        match (self.normalized(), other.normalized()) {
+            (Some(numbers), Some(other_numbers)) => numbers == other_numbers,
+            _ => self.raw == other.raw,
+        }
+    }
+}
+
+impl PartialOrd for FsVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self.normalized(), other.normalized()) {
+            (Some(numbers), Some(other_numbers)) => Some(numbers.cmp(other_numbers)),
+            _ if self.raw == other.raw => Some(Ordering::Equal),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for FsVersion {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::parse(s))
+    }
+}
+
+impl fmt::Display for FsVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.raw)
+    }
+}
+
+/// Identifiers of ISO9660 and UDF media gathered from the superblocks chain
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IsoInfo {
+    label: Option<String>,
+    system_id: Option<String>,
+    publisher_id: Option<String>,
+    application_id: Option<String>,
+    boot_system_id: Option<String>,
+    volume_id: Option<String>,
+    volume_set_id: Option<String>,
+    data_preparer_id: Option<String>,
+}
+
+impl IsoInfo {
+    /// Collects the identifiers from the probing result, `None` if `TYPE` is neither `iso9660`
+    /// nor `udf`
+    pub fn from_result(result: &ProbeResult) -> Option<Self> {
+        match result.fs_type()? {
+            FsType::Iso9660 | FsType::Udf => {}
+            _ => return None,
+        }
+
+        let owned = |value: Option<&str>| value.map(str::to_owned);
+        Some(Self {
+            label: owned(result.label()),
+            system_id: owned(result.get(SuperblockTag::SystemId)),
+            publisher_id: owned(result.get(SuperblockTag::PublisherId)),
+            application_id: owned(result.get(SuperblockTag::ApplicationId)),
+            boot_system_id: owned(result.get(SuperblockTag::BootSystemId)),
+            volume_id: owned(result.get(SuperblockTag::VolumeId)),
+            volume_set_id: owned(result.get(SuperblockTag::VolumeSetId)),
+            data_preparer_id: owned(result.get(SuperblockTag::DataPreparerId)),
+        })
+    }
+
+    /// Volume label
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    /// System identifier (e.g. "LINUX")
+    pub fn system_id(&self) -> Option<&str> {
+        self.system_id.as_deref()
+    }
+
+    /// Publisher identifier
+    pub fn publisher_id(&self) -> Option<&str> {
+        self.publisher_id.as_deref()
+    }
+
+    /// Application identifier (usually the authoring tool)
+    pub fn application_id(&self) -> Option<&str> {
+        self.application_id.as_deref()
+    }
+
+    /// El Torito boot system identifier, present on bootable media only
+    pub fn boot_system_id(&self) -> Option<&str> {
+        self.boot_system_id.as_deref()
+    }
+
+    /// Volume identifier
+    pub fn volume_id(&self) -> Option<&str> {
+        self.volume_id.as_deref()
+    }
+
+    /// Volume set identifier
+    pub fn volume_set_id(&self) -> Option<&str> {
+        self.volume_set_id.as_deref()
+    }
+
+    /// Data preparer identifier
+    pub fn data_preparer_id(&self) -> Option<&str> {
+        self.data_preparer_id.as_deref()
+    }
+}
+
+/// Pool metadata of a ZFS member device (`TYPE=zfs_member`)
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ZfsInfo {
+    pool_name: Option<String>,
+    pool_guid: Option<u64>,
+    vdev_guid: Option<u64>,
+    version: Option<String>,
+}
+
+impl ZfsInfo {
+    /// Collects the pool metadata from the probing result, `None` if the device is not a ZFS
+    /// member. Such devices are owned by ZFS and must not be repartitioned or formatted.
+    ///
+    /// `libblkid` reports the pool name as `LABEL`, the pool GUID as `UUID` and the vdev GUID as
+    /// `UUID_SUB` (both in decimal).
+    pub fn from_result(result: &ProbeResult) -> Option<Self> {
+        if result.fs_type()? != FsType::ZfsMember {
+            return None;
+        }
+
+        Some(Self {
+            pool_name: result.label().map(str::to_owned),
+            pool_guid: result.uuid().and_then(|guid| guid.parse().ok()),
+            vdev_guid: result.uuid_sub().and_then(|guid| guid.parse().ok()),
+            version: result.get(SuperblockTag::Version).map(str::to_owned),
+        })
+    }
+
+    /// Name of the pool
+    pub fn pool_name(&self) -> Option<&str> {
+        self.pool_name.as_deref()
+    }
+
+    /// GUID of the pool, shared by all member devices
+    pub fn pool_guid(&self) -> Option<u64> {
+        self.pool_guid
+    }
+
+    /// GUID of the vdev, unique per member device
+    pub fn vdev_guid(&self) -> Option<u64> {
+        self.vdev_guid
+    }
+
+    /// On-disk format version
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+}
+
+/// LVM physical volume label (`TYPE=LVM2_member`)
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LvmPvInfo {
+    uuid: Option<String>,
+    version: Option<String>,
+}
+
+impl LvmPvInfo {
+    /// Collects the physical volume details from the probing result, `None` if the device is not
+    /// an LVM physical volume
+    pub fn from_result(result: &ProbeResult) -> Option<Self> {
+        if !result.is_lvm_pv() {
+            return None;
+        }
+
+        Some(Self {
+            uuid: result.uuid().map(str::to_owned),
+            version: result.get(SuperblockTag::Version).map(str::to_owned),
+        })
+    }
+
+    /// PV UUID as formatted by `libblkid` and LVM tools (e.g. `Oa5bX0-1bLN-...`)
+    pub fn uuid(&self) -> Option<&str> {
+        self.uuid.as_deref()
+    }
+
+    /// PV UUID without dashes, the form stored in the LVM label (32 characters)
+    pub fn uuid_normalized(&self) -> Option<String> {
+        self.uuid
+            .as_ref()
+            .map(|uuid| uuid.chars().filter(|c| *c != '-').collect())
+    }
+
+    /// Label version (e.g. `LVM2 001`)
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+}
+
+/// Member of a bcache (`TYPE=bcache`) or bcachefs (`TYPE=bcachefs`) stack
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BcacheInfo {
+    fs_type: FsType,
+    uuid: Option<String>,
+    uuid_sub: Option<String>,
+    label: Option<String>,
+}
+
+impl BcacheInfo {
+    /// Collects the bcache details from the probing result, `None` for other devices
+    pub fn from_result(result: &ProbeResult) -> Option<Self> {
+        let fs_type = result.fs_type()?;
+        if !matches!(fs_type, FsType::Bcache | FsType::Bcachefs) {
+            return None;
+        }
+
+        Some(Self {
+            fs_type,
+            uuid: result.uuid().map(str::to_owned),
+            uuid_sub: result.uuid_sub().map(str::to_owned),
+            label: result.label().map(str::to_owned),
+        })
+    }
+
+    /// Returns `true` for bcachefs members, `false` for bcache backing or caching devices
+    pub fn is_bcachefs(&self) -> bool {
+        self.fs_type == FsType::Bcachefs
+    }
+
+    /// UUID of the device itself (`UUID` for bcache, `UUID_SUB` for bcachefs)
+    pub fn device_uuid(&self) -> Option<&str> {
+        if self.is_bcachefs() {
+            self.uuid_sub.as_deref()
+        } else {
+            self.uuid.as_deref()
+        }
+    }
+
+    /// UUID of the bcachefs filesystem shared by all its members, `None` for bcache devices
+    pub fn set_uuid(&self) -> Option<&str> {
+        if self.is_bcachefs() {
+            self.uuid.as_deref()
+        } else {
+            None
+        }
+    }
+
+    /// Label of the device or filesystem
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+}
+
+/// Evidence that the device is used by a Ceph OSD
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OsdHint {
+    /// BlueStore block device label (`TYPE=ceph_bluestore`)
+    Bluestore,
+    /// LVM physical volume with a logical volume created by `ceph-volume` on top. Contains the
+    /// device-mapper name of the volume (e.g. `ceph--<vg uuid>-osd--block--<osd fsid>`)
+    CephVolume(String),
+}
+
+/// Checks whether the device is used by a Ceph OSD, so orchestration tools do not provision it
+/// again. Returns `None` if no Ceph signature was found.
+///
+/// Besides `TYPE=ceph_bluestore` it checks device-mapper names of logical volumes stacked on
+/// top of LVM physical volumes, because `ceph-volume` keeps the OSD metadata in LVM tags which
+/// are not visible to `libblkid`.
+pub fn osd_hint(device: &DeviceInfo) -> BlkIdResult<Option<OsdHint>> {
+    let prober = Prober::new_from_filename(device.path())?;
+    let fs_type = match prober.do_safe_probe()? {
+        ProbeState::Success => prober.result()?.fs_type(),
+        _ => None,
+    };
+
+    match fs_type {
+        Some(FsType::CephBluestore) => Ok(Some(OsdHint::Bluestore)),
+        Some(FsType::Lvm2Member) => {
+            let stack = device.holders_tree()?;
+            Ok(stack
+                .devices()
+                .into_iter()
+                .filter_map(DeviceInfo::dm_name)
+                .find(|name| name.starts_with(CEPH_VOLUME_DM_PREFIX))
+                .map(OsdHint::CephVolume))
+        }
+        Some(_) => Ok(None),
+        None => {
+            let mut label = vec![0; BLUESTORE_LABEL.len()];
+            let is_bluestore = device.size() >= label.len() as u64
+                && prober.read_exact_at(&mut label, 0).is_ok()
+                && label == BLUESTORE_LABEL;
+            Ok(is_bluestore.then_some(OsdHint::Bluestore))
+        }
+    }
+}
+
+/// Returns every device of the btrfs filesystem `fs_uuid`.
+///
+/// All members of a multi-device btrfs share the same `UUID`, the members differ in
+/// [`ProbeResult::uuid_sub`] only.
+pub fn find_btrfs_members<S>(source: &S, fs_uuid: &str) -> BlkIdResult<Vec<ScannedDevice>>
+where
+    S: DeviceSource + ?Sized,
+{
+    find_by_uuid(source, FsType::Btrfs, fs_uuid)
+}
+
+/// Returns the external log device of the XFS filesystem described by `data`, `None` if the
+/// filesystem uses an internal log or the log device is not found in `source`.
+///
+/// The log device (`TYPE=xfs_external_log`) is matched by the [`ProbeResult::log_uuid`] of the
+/// data device.
+pub fn find_xfs_log_device<S>(source: &S, data: &ProbeResult) -> BlkIdResult<Option<ScannedDevice>>
+where
+    S: DeviceSource + ?Sized,
+{
+    match (data.fs_type(), data.log_uuid()) {
+        (Some(FsType::Xfs), Some(log_uuid)) => {
+            Ok(find_by_uuid(source, FsType::XfsExternalLog, log_uuid)?
+                .into_iter()
+                .next())
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Returns the external journal device of the ext3/ext4 filesystem described by `data`, `None` if
+/// the filesystem uses an internal journal or the journal device is not found in `source`.
+///
+/// The journal device (`TYPE=jbd`) is matched by the [`ProbeResult::ext_journal`] of the
+/// filesystem. Run the lookup before `fsck`, which needs the journal to replay it.
+pub fn find_ext_journal_device<S>(
+    source: &S,
+    data: &ProbeResult,
+) -> BlkIdResult<Option<ScannedDevice>>
+where
+    S: DeviceSource + ?Sized,
+{
+    match (data.fs_type(), data.ext_journal()) {
+        (Some(FsType::Ext3 | FsType::Ext4 | FsType::Ext4dev), Some(journal_uuid)) => {
+            Ok(find_by_uuid(source, FsType::Jbd, journal_uuid)?
+                .into_iter()
+                .next())
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Returns devices of the specified type with the `UUID` (case-insensitive)
+fn find_by_uuid<S>(source: &S, fs_type: FsType, uuid: &str) -> BlkIdResult<Vec<ScannedDevice>>
+where
+    S: DeviceSource + ?Sized,
+{
+    Ok(source
+        .scanned_devices()?
+        .into_iter()
+        .filter(|device| {
+            let result = device.result();
+            result.fs_type().as_ref() == Some(&fs_type)
+                && result
+                    .uuid()
+                    .is_some_and(|value| value.eq_ignore_ascii_case(uuid))
+        })
+        .collect())
+}
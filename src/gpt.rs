@@ -0,0 +1,381 @@
+//! Helpers for GUID Partition Table structures which are not covered by the partitions chain
+use crate::{prober::Prober, BlkIdResult};
+
+const GPT_SIGNATURE: &[u8] = b"EFI PART";
+const MBR_SIZE: usize = 512;
+const MBR_SIGNATURE: &[u8] = &[0x55, 0xAA];
+const MBR_ENTRIES_OFFSET: usize = 446;
+const MBR_ENTRY_SIZE: usize = 16;
+const MBR_GPT_PROTECTIVE_TYPE: u8 = 0xEE;
+const GPT_HEADER_MIN_SIZE: usize = 92;
+/// Sanity limit of the partition entries array, the UEFI spec requires at least 16 KiB
+const GPT_ENTRIES_MAX_LEN: u64 = 1 << 20;
+
+/// EFI System Partition type GUID, in the lower case form of `PART_ENTRY_TYPE`
+pub const EFI_SYSTEM_TYPE: &str = "c12a7328-f81f-11d2-ba4b-00a0c93ec93b";
+/// BIOS boot partition (GRUB core image on GPT disks) type GUID
+pub const BIOS_BOOT_TYPE: &str = "21686148-6449-6e6f-744e-656564454649";
+/// Linux swap type GUID
+pub const LINUX_SWAP_TYPE: &str = "0657fd6d-a4ab-43c4-84e5-0933c84b4f4f";
+/// Linux filesystem data type GUID
+pub const LINUX_FILESYSTEM_TYPE: &str = "0fc63daf-8483-4772-8e79-3d69d8477de4";
+/// Linux LVM physical volume type GUID
+pub const LINUX_LVM_TYPE: &str = "e6d6d379-f507-44c2-a23c-238f2a3df928";
+/// Linux software RAID member type GUID
+pub const LINUX_RAID_TYPE: &str = "a19d880f-05fc-4d3b-a006-743f0f84911e";
+/// Microsoft basic data (NTFS, exFAT, FAT) type GUID
+pub const MICROSOFT_BASIC_DATA_TYPE: &str = "ebd0a0a2-b9e5-4433-87c0-68b6b72699c7";
+/// Microsoft reserved partition type GUID
+pub const MICROSOFT_RESERVED_TYPE: &str = "e3c9e316-0b5c-4db8-817d-f92df00215ae";
+
+/// GPT header fields needed to locate the tables
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct GptHeader {
+    pub(crate) my_lba: u64,
+    pub(crate) alternate_lba: u64,
+    pub(crate) disk_guid: [u8; 16],
+    pub(crate) entries_lba: u64,
+    pub(crate) num_entries: u32,
+    pub(crate) entry_size: u32,
+}
+
+impl GptHeader {
+    /// Parses header from the begin of the sector, `None` if there is no GPT signature
+    pub(crate) fn parse(sector: &[u8]) -> Option<Self> {
+        if sector.len() < GPT_HEADER_MIN_SIZE || &sector[..8] != GPT_SIGNATURE {
+            return None;
+        }
+
+        let u32_at = |pos: usize| u32::from_le_bytes(sector[pos..pos + 4].try_into().unwrap());
+        let u64_at = |pos: usize| u64::from_le_bytes(sector[pos..pos + 8].try_into().unwrap());
+
+        Some(Self {
+            my_lba: u64_at(24),
+            alternate_lba: u64_at(32),
+            disk_guid: sector[56..72].try_into().unwrap(),
+            entries_lba: u64_at(72),
+            num_entries: u32_at(80),
+            entry_size: u32_at(84),
+        })
+    }
+
+    fn entries_len(&self) -> u64 {
+        u64::from(self.num_entries) * u64::from(self.entry_size)
+    }
+}
+
+/// Location of the backup GPT header and its partition entries array
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BackupGpt {
+    header_offset: u64,
+    header_len: u64,
+    entries_offset: u64,
+    entries_len: u64,
+}
+
+impl BackupGpt {
+    /// Offset of the header in bytes, relative to the probing area
+    pub fn header_offset(&self) -> u64 {
+        self.header_offset
+    }
+
+    /// Size of the header sector in bytes
+    pub fn header_len(&self) -> u64 {
+        self.header_len
+    }
+
+    /// Offset of the partition entries array in bytes, relative to the probing area
+    pub fn entries_offset(&self) -> u64 {
+        self.entries_offset
+    }
+
+    /// Size of the partition entries array in bytes
+    pub fn entries_len(&self) -> u64 {
+        self.entries_len
+    }
+}
+
+/// Consistency of the primary and backup GPT headers
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BackupGptStatus {
+    /// Neither primary nor backup GPT header was found
+    NoGpt,
+    /// Both headers exist and refer to each other
+    Consistent,
+    /// Primary header exists, but there is no backup header in the last sector
+    MissingBackup,
+    /// Backup header exists, but the primary one is missing (e.g. half-wiped disk)
+    Orphaned(BackupGpt),
+    /// Both headers exist, but they do not describe the same table (different disk GUIDs or
+    /// LBAs which do not refer to each other)
+    Mismatched(BackupGpt),
+    /// Primary header expects the backup header elsewhere than in the last sector (e.g. the disk
+    /// was resized). Contains the expected offset in bytes, relative to the probing area
+    Misplaced(u64),
+}
+
+/// Kind of the MBR in front of a GPT
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ProtectiveMbr {
+    /// Proper protective MBR: a single `0xEE` entry starting at LBA 1
+    Protective,
+    /// `0xEE` entry together with other entries (hybrid MBR, e.g. for dual-booting)
+    Hybrid,
+    /// `0xEE` entry which does not start at LBA 1 or more `0xEE` entries
+    Unusual,
+    /// Valid MBR without `0xEE` entry, i.e. legacy tools see a regular DOS partition table
+    NotProtective,
+    /// No MBR boot signature at all
+    Missing,
+}
+
+impl ProtectiveMbr {
+    fn parse(mbr: &[u8]) -> Self {
+        if mbr.len() < MBR_SIZE || &mbr[510..512] != MBR_SIGNATURE {
+            return Self::Missing;
+        }
+
+        let entries = mbr[MBR_ENTRIES_OFFSET..510]
+            .chunks_exact(MBR_ENTRY_SIZE)
+            .filter(|entry| entry[4] != 0)
+            .collect::<Vec<_>>();
+        let protective = entries
+            .iter()
+            .filter(|entry| entry[4] == MBR_GPT_PROTECTIVE_TYPE)
+            .collect::<Vec<_>>();
+
+        match (protective.as_slice(), entries.len()) {
+            ([], _) => Self::NotProtective,
+            ([entry], 1) if u32::from_le_bytes(entry[8..12].try_into().unwrap()) == 1 => {
+                Self::Protective
+            }
+            ([_], _) if entries.len() > 1 => Self::Hybrid,
+            _ => Self::Unusual,
+        }
+    }
+}
+
+impl Prober {
+    /// Inspects the MBR in the first sector of the probing area. The result is meaningful for GPT
+    /// disks only, see also [`crate::part_table::PartTableInfo::protective_mbr`].
+    pub fn protective_mbr(&self) -> BlkIdResult<ProtectiveMbr> {
+        let mut mbr = vec![0; MBR_SIZE];
+        self.read_exact_at(&mut mbr, self.get_offset()? as u64)?;
+        Ok(ProtectiveMbr::parse(&mbr))
+    }
+
+    /// Compares the primary GPT header (LBA 1) with the backup header in the last sector of the
+    /// probing area. Repair tools should warn about all states except [`BackupGptStatus::NoGpt`]
+    /// and [`BackupGptStatus::Consistent`].
+    pub fn check_backup_gpt(&self) -> BlkIdResult<BackupGptStatus> {
+        let sector_size = u64::from(self.get_sector_size());
+        let size = self.get_size()? as u64;
+        if size < sector_size * 2 {
+            return Ok(BackupGptStatus::NoGpt);
+        }
+
+        let last_lba = size / sector_size - 1;
+        let primary = self.read_gpt_header(1)?;
+        let backup_header = self.read_gpt_header(last_lba)?;
+        let backup = self.find_backup_gpt()?;
+
+        let status = match (primary, backup_header.zip(backup)) {
+            (None, None) => BackupGptStatus::NoGpt,
+            (None, Some((_, backup))) => BackupGptStatus::Orphaned(backup),
+            (Some(primary), _) if primary.alternate_lba != last_lba => {
+                BackupGptStatus::Misplaced(primary.alternate_lba.saturating_mul(sector_size))
+            }
+            (Some(_), None) => BackupGptStatus::MissingBackup,
+            (Some(primary), Some((header, backup))) => {
+                if header.disk_guid != primary.disk_guid || header.alternate_lba != primary.my_lba {
+                    BackupGptStatus::Mismatched(backup)
+                } else {
+                    BackupGptStatus::Consistent
+                }
+            }
+        };
+
+        Ok(status)
+    }
+
+    /// Locates the backup GPT header in the last logical sector of the probing area and its
+    /// partition entries array. Returns `None` if there is no backup GPT header.
+    ///
+    /// It uses [`Self::get_size`] and [`Self::get_sector_size`], so it does not depend on the
+    /// primary GPT which may be already wiped.
+    pub fn find_backup_gpt(&self) -> BlkIdResult<Option<BackupGpt>> {
+        let sector_size = u64::from(self.get_sector_size());
+        let size = self.get_size()? as u64;
+        if size < sector_size * 2 {
+            return Ok(None);
+        }
+
+        let last_lba = size / sector_size - 1;
+        let header = match self.read_gpt_header(last_lba)? {
+            Some(header) => header,
+            None => return Ok(None),
+        };
+
+        let entries_offset = header.entries_lba.saturating_mul(sector_size);
+        let entries_len = header.entries_len();
+        let entries_valid = entries_len <= GPT_ENTRIES_MAX_LEN
+            && entries_offset
+                .checked_add(entries_len)
+                .is_some_and(|end| end <= last_lba * sector_size);
+
+        Ok(Some(BackupGpt {
+            header_offset: last_lba * sector_size,
+            header_len: sector_size,
+            entries_offset: if entries_valid { entries_offset } else { 0 },
+            entries_len: if entries_valid { entries_len } else { 0 },
+        }))
+    }
+
+    /// Zeroes the backup GPT header and its partition entries array. Use it together with the
+    /// primary GPT wipe, otherwise some firmwares restore the primary GPT from the backup.
+    ///
+    /// The prober has to be assigned to a device opened in `O_RDWR` mode. Nothing is written in
+    /// `dry_run` mode. Returns the wiped location or `None` if there is no backup GPT.
+    pub fn wipe_backup_gpt(&self, dry_run: bool) -> BlkIdResult<Option<BackupGpt>> {
+        let backup = self.find_backup_gpt()?;
+
+        if let (false, Some(backup)) = (dry_run, &backup) {
+            let area_offset = self.get_offset()? as u64;
+            if backup.entries_len > 0 {
+                let zeroes = vec![0; backup.entries_len as usize];
+                self.write_all_at(&zeroes, area_offset + backup.entries_offset)?;
+            }
+            let zeroes = vec![0; backup.header_len as usize];
+            self.write_all_at(&zeroes, area_offset + backup.header_offset)?;
+        }
+
+        Ok(backup)
+    }
+
+    /// Reads GPT header from the logical sector `lba` of the probing area
+    pub(crate) fn read_gpt_header(&self, lba: u64) -> BlkIdResult<Option<GptHeader>> {
+        let sector_size = u64::from(self.get_sector_size());
+        let mut sector = vec![0; sector_size as usize];
+        self.read_exact_at(&mut sector, self.get_offset()? as u64 + lba * sector_size)?;
+        Ok(GptHeader::parse(&sector))
+    }
+}
+
+/// Conversions from and to the types of the [`gpt`](https://docs.rs/gpt) crate
+#[cfg(feature = "gpt")]
+mod interop {
+    use crate::{
+        part_table::{PartTableInfo, PartitionTableType},
+        partition::Partition,
+        report::PartitionInfo,
+        BlkIdError, BlkIdResult,
+    };
+    use ::gpt::{
+        disk::LogicalBlockSize,
+        header::Header,
+        partition_types::{OperatingSystem, Type},
+    };
+    use uuid::Uuid;
+
+    /// `libblkid` reports partition positions in 512-byte sectors regardless of the device
+    const SECTOR_SIZE: u64 = 512;
+
+    fn parse_guid(guid: Option<&str>) -> BlkIdResult<Uuid> {
+        let guid = guid.unwrap_or_default();
+        Uuid::parse_str(guid).map_err(|_| BlkIdError::InvalidGuid(guid.to_owned()))
+    }
+
+    impl PartitionInfo {
+        /// Converts a GPT entry of the `gpt` crate. `partno` is the key of the entry in
+        /// `GptDisk::partitions` and `lb_size` the logical block size of the disk.
+        pub fn from_gpt(
+            partno: u32,
+            partition: &::gpt::partition::Partition,
+            lb_size: LogicalBlockSize,
+        ) -> Self {
+            let lb_size: u64 = lb_size.into();
+            let blocks = partition.last_lba.saturating_sub(partition.first_lba) + 1;
+
+            Self {
+                partno: partno as i32,
+                start: (partition.first_lba * lb_size / SECTOR_SIZE) as i64,
+                size: (blocks * lb_size / SECTOR_SIZE) as i64,
+                typ: Some(partition.part_type_guid.guid.to_string()),
+                uuid: Some(partition.part_guid.to_string()),
+                name: Some(partition.name.clone()).filter(|name| !name.is_empty()),
+                flags: partition.flags,
+            }
+        }
+
+        /// Converts the partition to a GPT entry of the `gpt` crate, with LBAs in `lb_size`
+        /// blocks. Fails for partitions of other tables than GPT (their type or UUID is not a
+        /// GUID) and for partitions which are not aligned to `lb_size`.
+        pub fn to_gpt(
+            &self,
+            lb_size: LogicalBlockSize,
+        ) -> BlkIdResult<::gpt::partition::Partition> {
+            let block_size: u64 = lb_size.into();
+            let start = self.start as u64 * SECTOR_SIZE;
+            let size = self.size as u64 * SECTOR_SIZE;
+            if self.size <= 0 || start % block_size != 0 || size % block_size != 0 {
+                return Err(BlkIdError::UnalignedPartition {
+                    start: self.start,
+                    size: self.size,
+                    block_size,
+                });
+            }
+
+            let type_guid = parse_guid(self.typ())?;
+            let part_type_guid = Type::from_uuid(&type_guid).unwrap_or(Type {
+                guid: type_guid,
+                os: OperatingSystem::None,
+            });
+
+            Ok(::gpt::partition::Partition {
+                part_type_guid,
+                part_guid: parse_guid(self.uuid())?,
+                first_lba: start / block_size,
+                last_lba: (start + size) / block_size - 1,
+                flags: self.flags,
+                name: self.name.clone().unwrap_or_default(),
+            })
+        }
+    }
+
+    impl Partition<'_> {
+        /// Converts the partition to a GPT entry of the `gpt` crate, see
+        /// [`PartitionInfo::to_gpt`]
+        pub fn to_gpt(
+            &self,
+            lb_size: LogicalBlockSize,
+        ) -> BlkIdResult<::gpt::partition::Partition> {
+            PartitionInfo::new(self)?.to_gpt(lb_size)
+        }
+    }
+
+    impl PartTableInfo {
+        /// Converts the header of the `gpt` crate, `lb_size` is the logical block size of the
+        /// disk
+        pub fn from_gpt_header(header: &Header, lb_size: LogicalBlockSize) -> Self {
+            let lb_size: u64 = lb_size.into();
+
+            Self {
+                typ: Some(PartitionTableType::Gpt),
+                id: Some(header.disk_guid.to_string()),
+                offset: (header.current_lba * lb_size) as i64,
+                parent_partno: None,
+                protective_mbr: None,
+            }
+        }
+
+        /// Disk GUID of a GPT, the same as `Header::disk_guid` of the `gpt` crate. `None` for
+        /// other partition table types
+        pub fn gpt_disk_guid(&self) -> Option<Uuid> {
+            if self.typ != Some(PartitionTableType::Gpt) {
+                return None;
+            }
+            parse_guid(self.id()).ok()
+        }
+    }
+}
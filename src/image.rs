@@ -0,0 +1,129 @@
+//! Detection of virtual machine disk image formats
+use crate::{
+    classify::{classify, ContentKind},
+    BlkIdResult,
+};
+#[cfg(feature = "loopdev")]
+use crate::{
+    prober::Prober,
+    scan::{probe_device, ScannedDevice},
+    sysfs::DeviceInfo,
+    OpenFlags,
+};
+#[cfg(feature = "loopdev")]
+use loopdev::{LoopControl, LoopDevice};
+use std::{
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    path::Path,
+};
+
+const QCOW_MAGIC: &[u8] = b"QFI\xfb";
+const VDI_SIGNATURE_OFFSET: usize = 0x40;
+const VDI_SIGNATURE: &[u8] = &[0x7f, 0x10, 0xda, 0xbe];
+const VMDK_SPARSE_MAGIC: &[u8] = b"KDMV";
+const VMDK_DESCRIPTOR: &[u8] = b"# Disk DescriptorFile";
+const VHDX_MAGIC: &[u8] = b"vhdxfile";
+const VHD_COOKIE: &[u8] = b"conectix";
+const VHD_FOOTER_SIZE: u64 = 512;
+const HEADER_SIZE: usize = 512;
+
+/// Container format of a disk image file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ImageFormat {
+    /// QEMU copy-on-write image
+    Qcow2,
+    /// VirtualBox disk image
+    Vdi,
+    /// VMware disk (sparse extent or text descriptor)
+    Vmdk,
+    /// Virtual PC / Hyper-V disk
+    Vhd,
+    /// Hyper-V disk
+    Vhdx,
+    /// Raw image, contains result of [`classify`] of the image content
+    Raw(ContentKind),
+}
+
+/// Classifies the image file. Container formats are recognized by their magic strings, because
+/// `libblkid` does not probe them. Raw images are classified by the superblocks and partitions
+/// chains.
+pub fn detect_format<P: AsRef<Path>>(path: P) -> BlkIdResult<ImageFormat> {
+    let path = path.as_ref();
+    let mut file = File::open(path)?;
+
+    let mut header = Vec::with_capacity(HEADER_SIZE);
+    (&mut file)
+        .take(HEADER_SIZE as u64)
+        .read_to_end(&mut header)?;
+
+    let format = if header.starts_with(QCOW_MAGIC) {
+        ImageFormat::Qcow2
+    } else if header.get(VDI_SIGNATURE_OFFSET..VDI_SIGNATURE_OFFSET + VDI_SIGNATURE.len())
+        == Some(VDI_SIGNATURE)
+    {
+        ImageFormat::Vdi
+    } else if header.starts_with(VMDK_SPARSE_MAGIC) || header.starts_with(VMDK_DESCRIPTOR) {
+        ImageFormat::Vmdk
+    } else if header.starts_with(VHDX_MAGIC) {
+        ImageFormat::Vhdx
+    } else if header.starts_with(VHD_COOKIE) || has_vhd_footer(&mut file)? {
+        ImageFormat::Vhd
+    } else {
+        ImageFormat::Raw(classify(path)?)
+    };
+
+    Ok(format)
+}
+
+/// Fixed VHD images are raw data followed by a footer in the last 512 bytes
+fn has_vhd_footer(file: &mut File) -> io::Result<bool> {
+    if file.metadata()?.len() < VHD_FOOTER_SIZE {
+        return Ok(false);
+    }
+
+    let mut cookie = [0; 8];
+    file.seek(SeekFrom::End(-(VHD_FOOTER_SIZE as i64)))?;
+    file.read_exact(&mut cookie)?;
+    Ok(cookie == VHD_COOKIE)
+}
+
+/// Attaches the raw image file read-only to a free loop device with partition scanning enabled,
+/// probes the partitions the kernel created for it and detaches the loop device again.
+///
+/// Use it instead of probing the file directly when the partitions have to be visible to the
+/// kernel (e.g. for `ioctl`s or stacked devices). The paths of the returned devices (e.g.
+/// `/dev/loop0p1`) are not valid anymore when the function returns. Requires `CAP_SYS_ADMIN`.
+#[cfg(feature = "loopdev")]
+pub fn attach_and_probe<P: AsRef<Path>>(path: P) -> BlkIdResult<Vec<ScannedDevice>> {
+    let device = LoopControl::open()?.next_free()?;
+    device
+        .with()
+        .read_only(true)
+        .part_scan(true)
+        .attach(path.as_ref())?;
+
+    // Detach even if the probing failed, the probing error takes precedence
+    let devices = probe_loop_partitions(&device);
+    let detached = device.detach();
+    let devices = devices?;
+    detached?;
+    Ok(devices)
+}
+
+#[cfg(feature = "loopdev")]
+fn probe_loop_partitions(device: &LoopDevice) -> BlkIdResult<Vec<ScannedDevice>> {
+    let path = device
+        .path()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "loop device has no device node"))?;
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+
+    DeviceInfo::from_name(&name)?
+        .partitions()?
+        .iter()
+        .map(|partition| {
+            let prober = Prober::open(partition.path(), OpenFlags::empty())?;
+            probe_device(&prober, partition.path())
+        })
+        .collect()
+}
@@ -1,20 +1,185 @@
 //! See https://mirrors.edge.kernel.org/pub/linux/utils/util-linux/v2.37/libblkid-docs/index.html
 //! for the reference manual to the FFI bindings
 
+#[macro_use]
+mod macros;
+
+pub mod batch;
 pub mod cache;
+pub mod cancel;
+pub mod classify;
 pub mod dev;
+pub mod device;
+pub mod devno;
+#[cfg(feature = "devicemapper")]
+pub mod dm;
+pub mod dps;
 pub mod error;
+pub mod format;
+pub mod fs;
+pub mod gpt;
+pub mod image;
 pub mod part_list;
 pub mod part_table;
 pub mod partition;
+pub mod probe_result;
 pub mod prober;
+pub mod report;
+pub mod retry;
+pub mod scan;
+pub mod sysfs;
 pub mod tag;
+pub mod tags;
 pub mod topology;
+pub mod util;
+pub mod version;
+pub mod volume_id;
+pub mod wipe;
 
 use bitflags::bitflags;
-use std::{ffi::CString, path::Path};
+use std::{
+    ffi::CString,
+    fs::{File, OpenOptions},
+    os::unix::fs::OpenOptionsExt,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        PoisonError, RwLock,
+    },
+};
 
+pub use batch::BatchProber;
+pub use cancel::CancellationToken;
+pub use classify::{classify, ContentKind};
 pub use error::{BlkIdError, BlkIdResult};
+pub use retry::RetryPolicy;
+#[cfg(feature = "futures")]
+pub use scan::scan_stream;
+pub use scan::{scan, scan_cancellable, DeviceSource, MemoizedScan, ScannedDevice};
+pub use sysfs::{
+    list_block_devices, list_block_devices_with, DeviceClass, DeviceInfo, DeviceStack, ScanOptions,
+};
+pub use version::{library_version, LibraryVersion};
+
+/// Path used instead of the `blkid.tab` cache file in read-only mode
+pub(crate) const READ_ONLY_CACHE_FILE: &str = "/dev/null";
+
+static READ_ONLY_MODE: AtomicBool = AtomicBool::new(false);
+
+/// Switches the crate to read-only mode for the rest of the process lifetime, it cannot be
+/// disabled again. Suitable for forensic or sandboxed (landlock, seccomp) environments.
+///
+/// In read-only mode the crate never writes anything:
+/// * [`cache::Cache`] keeps the cache in memory only, `blkid.tab` is neither read nor written
+/// * signature wipes (except dry runs) fail with [`BlkIdError::ReadOnlyMode`]
+/// * devices are never opened with [`OpenFlags::READ_WRITE`]
+pub fn enable_read_only_mode() {
+    READ_ONLY_MODE.store(true, Ordering::SeqCst);
+}
+
+/// Returns `true` if [`enable_read_only_mode`] was called
+pub fn read_only_mode() -> bool {
+    READ_ONLY_MODE.load(Ordering::SeqCst)
+}
+
+/// Returns [`BlkIdError::ReadOnlyMode`] if the read-only mode is enabled
+pub(crate) fn check_writable() -> BlkIdResult<()> {
+    if read_only_mode() {
+        Err(BlkIdError::ReadOnlyMode)
+    } else {
+        Ok(())
+    }
+}
+
+static SYSTEM_ROOTS: RwLock<Option<SystemRoots>> = RwLock::new(None);
+
+/// Locations of the `/sys`, `/proc` and `/dev` filesystems used by the enumeration helpers (e.g.
+/// [`list_block_devices`], [`scan`] or [`DeviceInfo`]).
+///
+/// Agents running in a container with the host's filesystems mounted elsewhere (e.g. under
+/// `/host`) can probe the host's devices after [`set_system_roots`]. Functions implemented by
+/// `libblkid` itself (e.g. [`devno::Devno::to_devname`] or [`cache::Cache`]) always use the
+/// real `/sys` and `/dev`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SystemRoots {
+    sys: PathBuf,
+    proc: PathBuf,
+    dev: PathBuf,
+}
+
+impl Default for SystemRoots {
+    fn default() -> Self {
+        Self {
+            sys: PathBuf::from("/sys"),
+            proc: PathBuf::from("/proc"),
+            dev: PathBuf::from("/dev"),
+        }
+    }
+}
+
+impl SystemRoots {
+    /// Default roots `/sys`, `/proc` and `/dev`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// All roots mounted under `prefix`, e.g. `/host/sys`, `/host/proc` and `/host/dev`
+    pub fn under<P: AsRef<Path>>(prefix: P) -> Self {
+        let prefix = prefix.as_ref();
+        Self {
+            sys: prefix.join("sys"),
+            proc: prefix.join("proc"),
+            dev: prefix.join("dev"),
+        }
+    }
+
+    /// Location of `sysfs`
+    pub fn sys<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.sys = path.into();
+        self
+    }
+
+    /// Location of `procfs`
+    pub fn proc<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.proc = path.into();
+        self
+    }
+
+    /// Location of the device nodes
+    pub fn dev<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.dev = path.into();
+        self
+    }
+
+    /// Location of `sysfs`, `/sys` by default
+    pub fn get_sys(&self) -> &Path {
+        &self.sys
+    }
+
+    /// Location of `procfs`, `/proc` by default
+    pub fn get_proc(&self) -> &Path {
+        &self.proc
+    }
+
+    /// Location of the device nodes, `/dev` by default
+    pub fn get_dev(&self) -> &Path {
+        &self.dev
+    }
+}
+
+/// Overrides the roots of the system filesystems for the whole process, see [`SystemRoots`]
+pub fn set_system_roots(roots: SystemRoots) {
+    *SYSTEM_ROOTS.write().unwrap_or_else(PoisonError::into_inner) = Some(roots);
+}
+
+/// Returns the roots set by [`set_system_roots`] or the default ones
+pub fn system_roots() -> SystemRoots {
+    SYSTEM_ROOTS
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+        .unwrap_or_default()
+}
 
 pub(crate) fn path_to_cstring<P: AsRef<Path>>(path: P) -> BlkIdResult<CString> {
     Ok(CString::new(path.as_ref().to_string_lossy().as_ref())?)
@@ -47,11 +212,82 @@ bitflags! {
         const DEFAULT   = Self::LABEL.bits | Self::UUID.bits | Self::TYPE.bits | Self::SECTYPE.bits;
     }
 
+    /// Flags used by the crate to open devices, in addition to `O_RDONLY`, `O_CLOEXEC` and
+    /// `O_NONBLOCK`.
+    ///
+    /// `O_NONBLOCK` follows the CD-ROM convention, so opening a removable drive without medium
+    /// does not wait for the medium and probing such drive fails with [`BlkIdError::NoMedium`].
+    #[derive(Default)]
+    pub struct OpenFlags: i32 {
+        /// Open for writing too (`O_RDWR`), required by the wipe functions
+        const READ_WRITE = libc::O_RDWR;
+        /// Bypass the page cache (`O_DIRECT`), so mass scans do not evict cached data of the host.
+        /// See [`prober::Prober::open`] for the limitations
+        const DIRECT     = libc::O_DIRECT;
+    }
+
     pub struct PartitionsFlags: i32 {
         const FORCE_GPT     = 1 << 1;
         const ENTRY_DETAILS = 1 << 2;
         const MAGIC         = 1 << 3;
     }
+
+    /// Usages of superblocks (the `USAGE` value) for [`prober::Prober::filter_superblocks_usage`]
+    pub struct UsageFlags: i32 {
+        /// Filesystems
+        const FILESYSTEM = 1 << 1;
+        /// RAID members (e.g. `linux_raid_member`, `LVM2_member`)
+        const RAID       = 1 << 2;
+        /// Encrypted devices (e.g. `crypto_LUKS`)
+        const CRYPTO     = 1 << 3;
+        /// Other signatures (e.g. `swap`)
+        const OTHER      = 1 << 4;
+    }
+}
+
+flag_names!(SuperblocksFlags {
+    LABEL,
+    LABELRAW,
+    UUID,
+    UUIDRAW,
+    TYPE,
+    SECTYPE,
+    USAGE,
+    VERSION,
+    MAGIC,
+    #[cfg(blkid = "2.24")]
+    BADCSUM,
+    DEFAULT,
+});
+
+flag_names!(PartitionsFlags {
+    FORCE_GPT,
+    ENTRY_DETAILS,
+    MAGIC,
+});
+
+flag_names!(UsageFlags {
+    FILESYSTEM,
+    RAID,
+    CRYPTO,
+    OTHER,
+});
+
+impl OpenFlags {
+    /// Opens the file for reading (and writing with [`Self::READ_WRITE`])
+    pub(crate) fn open<P: AsRef<Path>>(self, path: P) -> BlkIdResult<File> {
+        let path = path.as_ref();
+        if self.contains(Self::READ_WRITE) {
+            check_writable()?;
+        }
+
+        OpenOptions::new()
+            .read(true)
+            .write(self.contains(Self::READ_WRITE))
+            .custom_flags((self - Self::READ_WRITE).bits() | libc::O_NONBLOCK)
+            .open(path)
+            .map_err(|err| BlkIdError::from(err).with_path(path))
+    }
 }
 
 impl Default for SuperblocksFlags {
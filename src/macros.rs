@@ -0,0 +1,198 @@
+//! Internal helper macros
+
+/// Implements `Display` and `FromStr` (and serde support with the `serde` feature) for a
+/// `bitflags` type using comma separated flag names, e.g. `LABEL,UUID,TYPE`.
+///
+/// `Display` prints single-bit flags only, `FromStr` accepts composite flags (e.g. `DEFAULT`) too
+/// and ignores case and whitespace around the names.
+macro_rules! flag_names {
+    ($flags:ty { $($(#[$meta:meta])* $name:ident),+ $(,)? }) => {
+        impl $flags {
+            const NAMES: &'static [(&'static str, $flags)] = &[
+                $($(#[$meta])* (stringify!($name), <$flags>::$name),)+
+            ];
+        }
+
+        impl std::fmt::Display for $flags {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                let names = Self::NAMES
+                    .iter()
+                    .filter(|(_, flag)| flag.bits().count_ones() == 1 && self.contains(*flag))
+                    .map(|(name, _)| *name)
+                    .collect::<Vec<_>>();
+                write!(f, "{}", names.join(","))
+            }
+        }
+
+        impl std::str::FromStr for $flags {
+            type Err = $crate::BlkIdError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let mut flags = Self::empty();
+
+                for name in s.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+                    let (_, flag) = Self::NAMES
+                        .iter()
+                        .find(|(known, _)| known.eq_ignore_ascii_case(name))
+                        .ok_or_else(|| $crate::BlkIdError::InvalidFlag(name.to_owned()))?;
+                    flags |= *flag;
+                }
+                Ok(flags)
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl serde::Serialize for $flags {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl<'de> serde::Deserialize<'de> for $flags {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let names = String::deserialize(deserializer)?;
+                names.parse().map_err(serde::de::Error::custom)
+            }
+        }
+    };
+}
+
+/// Defines a unit-only enum mapped to the names used by `libblkid`, together with `as_str`,
+/// `Display`, `FromStr` and `AsRef<str>` (and serde support as the name with the `serde`
+/// feature).
+///
+/// The enum is closed unless the last entry is `_ => Unknown`, which adds `Unknown(String)`
+/// variant keeping names not covered by the other variants. `FromStr` of such enum never fails.
+macro_rules! string_enum {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            $($(#[$vmeta:meta])* $variant:ident = $value:literal,)+
+        }
+    ) => {
+        $(#[$meta])*
+        $vis enum $name {
+            $($(#[$vmeta])* $variant,)+
+        }
+
+        impl $name {
+            /// All variants
+            pub const ALL: &'static [Self] = &[$(Self::$variant,)+];
+
+            /// Name used by `libblkid`
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    $(Self::$variant => $value,)+
+                }
+            }
+        }
+
+        impl std::str::FromStr for $name {
+            type Err = $crate::BlkIdError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    $($value => Ok(Self::$variant),)+
+                    _ => Err($crate::BlkIdError::UnknownName(s.to_owned())),
+                }
+            }
+        }
+
+        string_enum!(@common $name);
+    };
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            $($(#[$vmeta:meta])* $variant:ident = $value:literal,)+
+            _ => $unknown:ident,
+        }
+    ) => {
+        $(#[$meta])*
+        $vis enum $name {
+            $($(#[$vmeta])* $variant,)+
+            /// Name not covered by the other variants
+            $unknown(String),
+        }
+
+        impl $name {
+            /// All variants except the unknown one
+            pub const ALL: &'static [Self] = &[$(Self::$variant,)+];
+
+            /// Name used by `libblkid`
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(Self::$variant => $value,)+
+                    Self::$unknown(name) => name,
+                }
+            }
+        }
+
+        impl std::str::FromStr for $name {
+            type Err = std::convert::Infallible;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Ok(match s {
+                    $($value => Self::$variant,)+
+                    _ => Self::$unknown(s.to_owned()),
+                })
+            }
+        }
+
+        string_enum!(@common $name);
+    };
+    (@common $name:ident) => {
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                self.as_str()
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl serde::Serialize for $name {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl<'de> serde::Deserialize<'de> for $name {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let name = String::deserialize(deserializer)?;
+                name.parse().map_err(serde::de::Error::custom)
+            }
+        }
+    };
+}
+
+/// Defines `&str` constants of the value names together with `ALL` (list of the names) and the
+/// same constants as `&CStr` in the `cstr` submodule, the constant name is the value name
+macro_rules! value_names {
+    ($($(#[$meta:meta])* $name:ident,)+) => {
+        $($(#[$meta])* pub const $name: &str = stringify!($name);)+
+
+        /// All value names above
+        pub const ALL: &[&str] = &[$($name,)+];
+
+        /// Names of the values as C strings for [`crate::prober::Prober::lookup_value_cstr`], so
+        /// hot probing loops do not allocate a `CString` per lookup
+        pub mod cstr {
+            use std::ffi::CStr;
+
+            $(
+                $(#[$meta])*
+                pub const $name: &CStr =
+                    match CStr::from_bytes_with_nul(concat!(stringify!($name), "\0").as_bytes()) {
+                        Ok(name) => name,
+                        Err(_) => panic!("value name contains NUL"),
+                    };
+            )+
+        }
+    };
+}
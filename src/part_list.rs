@@ -1,10 +1,116 @@
-use crate::{error::c_result, part_table::PartTable, partition::Partition, BlkIdResult};
+use crate::{
+    error::c_result,
+    part_table::{PartTable, PartTableInfo},
+    partition::Partition,
+    prober::Prober,
+    report::PartitionInfo,
+    topology::Topology,
+    BlkIdResult,
+};
 use blkid_sys::*;
+use std::marker::PhantomData;
+
+/// Size of the sectors used by [`Partition::start`] and [`Partition::size`]
+const SECTOR_SIZE: u64 = 512;
+
+/// Alignment of a partition start according to the device topology
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PartitionAlignment {
+    partno: i32,
+    start: u64,
+    grain: u64,
+    misalignment: u64,
+}
+
+impl PartitionAlignment {
+    /// Partition number (e.g. `N` from sda`N`)
+    pub fn partno(&self) -> i32 {
+        self.partno
+    }
+
+    /// Start of the partition in bytes
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    /// Alignment granularity in bytes the start was checked against
+    pub fn grain(&self) -> u64 {
+        self.grain
+    }
+
+    /// Returns `true` if the partition start is aligned
+    pub fn is_aligned(&self) -> bool {
+        self.misalignment == 0
+    }
+
+    /// Number of bytes the start is past the previous aligned position
+    pub fn misalignment(&self) -> u64 {
+        self.misalignment
+    }
+
+    /// The nearest aligned start (in bytes) which does not overlap the preceding data, the same
+    /// as [`Self::start`] for aligned partitions
+    pub fn suggested_start(&self) -> u64 {
+        if self.is_aligned() {
+            self.start
+        } else {
+            self.start - self.misalignment + self.grain
+        }
+    }
+}
+
+/// Owned copy of the partition table and all its partitions, see
+/// [`Prober::partitions_snapshot`]. Unlike [`PartList`] it stays valid after the prober is
+/// reused or dropped.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PartListSnapshot {
+    pub(crate) table: Option<PartTableInfo>,
+    pub(crate) partitions: Vec<PartitionInfo>,
+}
+
+impl PartListSnapshot {
+    /// Partition table, `None` if there is no partition table
+    pub fn table(&self) -> Option<&PartTableInfo> {
+        self.table.as_ref()
+    }
+
+    /// Partitions in the order of the partition table
+    pub fn partitions(&self) -> &[PartitionInfo] {
+        &self.partitions
+    }
+
+    /// Partition by the partition number (e.g. `N` from sda`N`)
+    pub fn partition_by_partno(&self, partno: i32) -> Option<&PartitionInfo> {
+        self.partitions
+            .iter()
+            .find(|partition| partition.partno() == partno)
+    }
+
+    /// Number of partitions
+    pub fn len(&self) -> usize {
+        self.partitions.len()
+    }
+
+    /// Returns `true` if there are no partitions (the partition table may still exist)
+    pub fn is_empty(&self) -> bool {
+        self.partitions.is_empty()
+    }
+}
 
 /// List of all detected partitions and partitions tables
-pub struct PartList(pub(crate) blkid_partlist);
+///
+/// The list borrows the [`Prober`] it was returned from mutably, so no other prober call (e.g.
+/// [`Prober::do_probe`], [`Prober::set_device`] or another [`Prober::part_list`]) which frees the
+/// partition tables can run while the list or any [`Partition`] or [`PartTable`] of it is alive.
+/// See [`PartListSnapshot`] for an owned copy.
+pub struct PartList<'a>(pub(crate) blkid_partlist, PhantomData<&'a mut Prober>);
+
+impl<'a> PartList<'a> {
+    pub(crate) fn new(list: blkid_partlist) -> Self {
+        Self(list, PhantomData)
+    }
 
-impl PartList {
     /// Returns partition object.
     ///
     /// It's possible that the list of partitions is *empty*, but there is a valid partition table
@@ -12,8 +118,8 @@ impl PartList {
     /// table is empty.
     ///
     /// See also [`Self::get_table`].
-    pub fn get_partition(&self, part_num: i32) -> BlkIdResult<Partition> {
-        unsafe { c_result(blkid_partlist_get_partition(self.0, part_num)).map(Partition) }
+    pub fn get_partition(&self, part_num: i32) -> BlkIdResult<Partition<'a>> {
+        unsafe { c_result(blkid_partlist_get_partition(self.0, part_num)).map(Partition::new) }
     }
 
     /// Returns partition object by the partiton number (e.g. `N` from sda`N`).
@@ -21,12 +127,14 @@ impl PartList {
     /// This does not assume any order of the input blkid_partlist. And correctly handles "out of
     /// order" partition tables. partition N is located after partition N+1 on the disk.
     #[cfg(blkid = "2.25")]
-    pub fn get_partition_by_parno(&self, partno: i32) -> BlkIdResult<Partition> {
-        unsafe { c_result(blkid_partlist_get_partition_by_partno(self.0, partno)).map(Partition) }
+    pub fn get_partition_by_parno(&self, partno: i32) -> BlkIdResult<Partition<'a>> {
+        unsafe {
+            c_result(blkid_partlist_get_partition_by_partno(self.0, partno)).map(Partition::new)
+        }
     }
 
     /// Returns all partitions
-    pub fn get_partitions(&self) -> BlkIdResult<Vec<Partition>> {
+    pub fn get_partitions(&self) -> BlkIdResult<Vec<Partition<'a>>> {
         let numof = self.numof_partitions()?;
         let mut partitions = Vec::with_capacity(numof as usize);
 
@@ -36,6 +144,49 @@ impl PartList {
         Ok(partitions)
     }
 
+    /// Returns all partitions ordered by their start (and by the partition number for the same
+    /// start). Partition tables are not required to be in the on-disk order, e.g. partition N can
+    /// be located after partition N+1.
+    pub fn sorted_by_start(&self) -> BlkIdResult<Vec<Partition<'a>>> {
+        let mut partitions = self
+            .get_partitions()?
+            .into_iter()
+            .map(|partition| Ok(((partition.start()?, partition.partno()?), partition)))
+            .collect::<BlkIdResult<Vec<_>>>()?;
+        partitions.sort_by_key(|(key, _)| *key);
+        Ok(partitions
+            .into_iter()
+            .map(|(_, partition)| partition)
+            .collect())
+    }
+
+    /// Returns primary partitions (for MBR the entries of the first sector)
+    pub fn primary_partitions(&self) -> BlkIdResult<Vec<Partition<'a>>> {
+        self.filter_partitions(Partition::is_primary)
+    }
+
+    /// Returns logical partitions (partitions inside of the extended partition)
+    pub fn logical_partitions(&self) -> BlkIdResult<Vec<Partition<'a>>> {
+        self.filter_partitions(Partition::is_logical)
+    }
+
+    /// Returns the extended partition, `None` if there is no one
+    pub fn extended_partition(&self) -> BlkIdResult<Option<Partition<'a>>> {
+        Ok(self
+            .get_partitions()?
+            .into_iter()
+            .find(Partition::is_extended))
+    }
+
+    fn filter_partitions<F>(&self, predicate: F) -> BlkIdResult<Vec<Partition<'a>>>
+    where
+        F: Fn(&Partition<'a>) -> bool,
+    {
+        let mut partitions = self.get_partitions()?;
+        partitions.retain(predicate);
+        Ok(partitions)
+    }
+
     /// Returns partition object by requested partition.
     ///
     /// This tries to get start and size for devno from `sysfs` and returns a partition from list
@@ -43,17 +194,17 @@ impl PartList {
     ///
     /// This function is necessary when you want to make a relation between an entry in the
     /// partition table (list) and block devices in your system.
-    pub fn devno_to_partition(&self, devno: u64) -> BlkIdResult<Partition> {
-        unsafe { c_result(blkid_partlist_devno_to_partition(self.0, devno)).map(Partition) }
+    pub fn devno_to_partition(&self, devno: u64) -> BlkIdResult<Partition<'a>> {
+        unsafe { c_result(blkid_partlist_devno_to_partition(self.0, devno)).map(Partition::new) }
     }
 
     /// Returns [`PartTable`] or `None` if there is not a partition table on the device
-    pub fn get_table(&self) -> Option<PartTable> {
+    pub fn get_table(&self) -> Option<PartTable<'a>> {
         let table = unsafe { blkid_partlist_get_table(self.0) };
         if table.is_null() {
             None
         } else {
-            Some(PartTable(table))
+            Some(PartTable::new(table))
         }
     }
 
@@ -61,4 +212,31 @@ impl PartList {
     pub fn numof_partitions(&self) -> BlkIdResult<i32> {
         unsafe { c_result(blkid_partlist_numof_partitions(self.0)) }
     }
+
+    /// Checks start of every partition against the device topology (e.g. from
+    /// [`crate::prober::Prober::topology`] of the same device).
+    ///
+    /// The granularity is the minimum I/O size or the physical sector size, whichever is bigger,
+    /// shifted by the alignment offset of the device.
+    pub fn alignment_report(&self, topology: &Topology) -> BlkIdResult<Vec<PartitionAlignment>> {
+        let grain = topology
+            .minimum_io_size()
+            .max(topology.physical_sector_size())
+            .max(topology.logical_sector_size())
+            .max(SECTOR_SIZE);
+        let shift = topology.alignment_offset() % grain;
+
+        self.get_partitions()?
+            .iter()
+            .map(|partition| {
+                let start = partition.start()? as u64 * SECTOR_SIZE;
+                Ok(PartitionAlignment {
+                    partno: partition.partno()?,
+                    start,
+                    grain,
+                    misalignment: (start + grain - shift) % grain,
+                })
+            })
+            .collect()
+    }
 }
@@ -1,13 +1,19 @@
-use crate::{error::c_result, partition::Partition, BlkIdResult};
+use crate::{
+    error::c_result, gpt::ProtectiveMbr, part_list::PartList, partition::Partition, prober::Prober,
+    BlkIdResult,
+};
 use blkid_sys::*;
-use std::{ffi::CStr, str::FromStr};
-use strum_macros::{Display, EnumString};
+use std::{ffi::CStr, marker::PhantomData, str::FromStr};
 
 /// Information about a partition table
 #[derive(Debug)]
-pub struct PartTable(pub(crate) blkid_parttable);
+pub struct PartTable<'a>(pub(crate) blkid_parttable, PhantomData<&'a mut Prober>);
+
+impl<'a> PartTable<'a> {
+    pub(crate) fn new(table: blkid_parttable) -> Self {
+        Self(table, PhantomData)
+    }
 
-impl PartTable {
     /// Returns partition table ID (for example GPT disk UUID).
     ///
     /// The ID is GPT disk UUID or DOS disk ID (in hex format).
@@ -32,13 +38,26 @@ impl PartTable {
     }
 
     /// Returns parent for nested partition tables
-    pub fn get_parent(&self) -> Option<Partition> {
+    pub fn get_parent(&self) -> Option<Partition<'a>> {
         let part = unsafe { blkid_parttable_get_parent(self.0) };
         if part.is_null() {
             None
         } else {
-            Some(Partition(part))
+            Some(Partition::new(part))
+        }
+    }
+
+    /// Returns partitions of `list` which belong to this table, the reverse of
+    /// [`Partition::table`]. Partitions of nested tables (e.g. BSD disklabel inside of a DOS
+    /// partition) belong to the nested table only, DOS logical partitions belong to the DOS table.
+    pub fn partitions(&self, list: &PartList<'a>) -> BlkIdResult<Vec<Partition<'a>>> {
+        let mut partitions = Vec::new();
+        for partition in list.get_partitions()? {
+            if partition.table()?.0 == self.0 {
+                partitions.push(partition);
+            }
         }
+        Ok(partitions)
     }
 
     /// Returns partition table type (type name, e.g. "dos", "gpt", ...)
@@ -53,23 +72,84 @@ impl PartTable {
             Some(part_table_type)
         }
     }
+
+    /// Returns owned snapshot of the table metadata which outlives the prober.
+    ///
+    /// [`PartTableInfo::protective_mbr`] is not filled, use [`Prober::part_table_info`] for it.
+    pub fn to_info(&self) -> BlkIdResult<PartTableInfo> {
+        #[cfg(blkid = "2.23")]
+        let id = self.get_id();
+        #[cfg(not(blkid = "2.23"))]
+        let id = None;
+        let parent_partno = match self.get_parent() {
+            Some(parent) => Some(parent.partno()?),
+            None => None,
+        };
+
+        Ok(PartTableInfo {
+            typ: self.get_type(),
+            id,
+            offset: self.get_offset()?,
+            parent_partno,
+            protective_mbr: None,
+        })
+    }
+}
+
+string_enum! {
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    pub enum PartitionTableType {
+        Aix = "aix",
+        Atari = "atari",
+        Bsd = "bsd",
+        Dos = "dos",
+        Gpt = "gpt",
+        Mac = "mac",
+        Minix = "minix",
+        Sgi = "sgi",
+        Solaris = "solaris",
+        Sun = "sun",
+        Ultrix = "ultrix",
+        Unixware = "unixware",
+        _ => Unknown,
+    }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq, Display, EnumString)]
-#[strum(serialize_all = "lowercase")]
-pub enum PartitionTableType {
-    Aix,
-    Atari,
-    Bsd,
-    Dos,
-    Gpt,
-    Mac,
-    Minix,
-    Sgi,
-    Solaris,
-    Sun,
-    Ultrix,
-    Unixware,
-    #[strum(default)]
-    Unknown(String),
+/// Owned report about a partition table
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PartTableInfo {
+    pub(crate) typ: Option<PartitionTableType>,
+    pub(crate) id: Option<String>,
+    pub(crate) offset: i64,
+    pub(crate) parent_partno: Option<i32>,
+    pub(crate) protective_mbr: Option<ProtectiveMbr>,
+}
+
+impl PartTableInfo {
+    /// Partition table type
+    pub fn typ(&self) -> Option<&PartitionTableType> {
+        self.typ.as_ref()
+    }
+
+    /// Partition table ID (GPT disk UUID or DOS disk ID)
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    /// Position (in bytes) of the partition table
+    pub fn offset(&self) -> i64 {
+        self.offset
+    }
+
+    /// Number of the partition which contains this nested table (e.g. BSD disklabel inside of a
+    /// DOS partition), `None` for the primary partition table
+    pub fn parent_partno(&self) -> Option<i32> {
+        self.parent_partno
+    }
+
+    /// Kind of the MBR in front of the GPT, `None` for other partition table types
+    pub fn protective_mbr(&self) -> Option<ProtectiveMbr> {
+        self.protective_mbr
+    }
 }
@@ -1,12 +1,31 @@
-use crate::{error::c_result, part_table::PartTable, BlkIdResult};
+use crate::{
+    error::c_result,
+    gpt,
+    part_table::{PartTable, PartitionTableType},
+    prober::Prober,
+    BlkIdResult,
+};
 use blkid_sys::*;
-use std::ffi::CStr;
+use std::{ffi::CStr, marker::PhantomData};
+
+/// DOS (MBR) system ids of the well-known partition types
+const DOS_EFI_SYSTEM: i32 = 0xef;
+const DOS_LINUX_SWAP: i32 = 0x82;
+const DOS_LINUX_FILESYSTEM: i32 = 0x83;
+const DOS_LINUX_LVM: i32 = 0x8e;
+const DOS_LINUX_RAID: i32 = 0xfd;
+/// FAT12, FAT16, NTFS/exFAT and FAT32 (CHS and LBA) ids
+const DOS_MICROSOFT_BASIC_DATA: &[i32] = &[0x01, 0x04, 0x06, 0x07, 0x0b, 0x0c, 0x0e];
 
 /// Information about a partition
 #[derive(Debug)]
-pub struct Partition(pub(crate) blkid_partition);
+pub struct Partition<'a>(pub(crate) blkid_partition, PhantomData<&'a mut Prober>);
+
+impl<'a> Partition<'a> {
+    pub(crate) fn new(partition: blkid_partition) -> Self {
+        Self(partition, PhantomData)
+    }
 
-impl Partition {
     /// Returns partition name some string if supported by PT (e.g. Mac) or None
     pub fn name(&self) -> Option<String> {
         let name = unsafe { blkid_partition_get_name(self.0) };
@@ -80,8 +99,8 @@ impl Partition {
     /// The library does not to use a separate partition table object for dos logical partitions
     /// (partitions within extended partition). It's possible to differentiate between logical,
     /// extended and primary partitions by `Self::is_{extended, primary, logical}`.
-    pub fn table(&self) -> BlkIdResult<PartTable> {
-        unsafe { c_result(blkid_partition_get_table(self.0)).map(PartTable) }
+    pub fn table(&self) -> BlkIdResult<PartTable<'a>> {
+        unsafe { c_result(blkid_partition_get_table(self.0)).map(PartTable::new) }
     }
 
     /// Returns partition type
@@ -135,4 +154,56 @@ impl Partition {
     pub fn is_primary(&self) -> bool {
         unsafe { blkid_partition_is_primary(self.0) == 1 }
     }
+
+    /// Returns `true` for EFI System Partition (ESP)
+    pub fn is_efi_system(&self) -> bool {
+        self.has_known_type(gpt::EFI_SYSTEM_TYPE, &[DOS_EFI_SYSTEM])
+    }
+
+    /// Returns `true` for BIOS boot partition (GPT only)
+    pub fn is_bios_boot(&self) -> bool {
+        self.has_known_type(gpt::BIOS_BOOT_TYPE, &[])
+    }
+
+    /// Returns `true` for Linux swap partition
+    pub fn is_linux_swap(&self) -> bool {
+        self.has_known_type(gpt::LINUX_SWAP_TYPE, &[DOS_LINUX_SWAP])
+    }
+
+    /// Returns `true` for Linux filesystem data partition
+    pub fn is_linux_filesystem(&self) -> bool {
+        self.has_known_type(gpt::LINUX_FILESYSTEM_TYPE, &[DOS_LINUX_FILESYSTEM])
+    }
+
+    /// Returns `true` for Linux LVM partition
+    pub fn is_linux_lvm(&self) -> bool {
+        self.has_known_type(gpt::LINUX_LVM_TYPE, &[DOS_LINUX_LVM])
+    }
+
+    /// Returns `true` for Linux software RAID partition
+    pub fn is_linux_raid(&self) -> bool {
+        self.has_known_type(gpt::LINUX_RAID_TYPE, &[DOS_LINUX_RAID])
+    }
+
+    /// Returns `true` for Microsoft basic data partition (for DOS tables any FAT or NTFS id)
+    pub fn is_microsoft_basic_data(&self) -> bool {
+        self.has_known_type(gpt::MICROSOFT_BASIC_DATA_TYPE, DOS_MICROSOFT_BASIC_DATA)
+    }
+
+    /// Returns `true` for Microsoft reserved partition (GPT only)
+    pub fn is_microsoft_reserved(&self) -> bool {
+        self.has_known_type(gpt::MICROSOFT_RESERVED_TYPE, &[])
+    }
+
+    /// Compares the type GUID for GPT and the system id for DOS partition tables. Partitions of
+    /// other partition tables never match
+    fn has_known_type(&self, type_guid: &str, dos_ids: &[i32]) -> bool {
+        match self.table().ok().and_then(|table| table.get_type()) {
+            Some(PartitionTableType::Gpt) => self
+                .typ_string()
+                .is_some_and(|typ| typ.eq_ignore_ascii_case(type_guid)),
+            Some(PartitionTableType::Dos) => dos_ids.contains(&self.typ()),
+            _ => false,
+        }
+    }
 }
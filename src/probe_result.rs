@@ -0,0 +1,130 @@
+use crate::{
+    fs::{FsType, FsVersion},
+    part_table::PartitionTableType,
+    tag::{PartitionTag, SuperblockTag, Tag, TagType},
+    volume_id::VolumeId,
+};
+use std::str::FromStr;
+
+/// Owned snapshot of the `NAME=value` probing results.
+///
+/// Unlike the values stored in [`crate::prober::Prober`] the snapshot is not overwritten by the
+/// next probing call.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProbeResult {
+    tags: Vec<Tag>,
+}
+
+impl ProbeResult {
+    /// All probed values in the order returned by `libblkid`
+    pub fn tags(&self) -> &[Tag] {
+        &self.tags
+    }
+
+    /// Returns `true` if nothing was detected
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+    }
+
+    /// Value of the specified tag
+    pub fn get(&self, tag_type: impl Into<TagType>) -> Option<&str> {
+        let tag_type = tag_type.into();
+        self.tags
+            .iter()
+            .find(|tag| tag.typ() == tag_type)
+            .map(|tag| tag.value())
+    }
+
+    /// Returns `true` if the result has the specified tag
+    pub fn contains(&self, tag_type: impl Into<TagType>) -> bool {
+        self.get(tag_type).is_some()
+    }
+
+    /// Filesystem (or other superblock) type
+    pub fn fs_type(&self) -> Option<FsType> {
+        self.get(SuperblockTag::Type).map(|fs_type| {
+            FsType::from_str(fs_type).unwrap_or_else(|_| FsType::Unknown(fs_type.to_owned()))
+        })
+    }
+
+    /// Returns `true` if the device is an LVM physical volume
+    pub fn is_lvm_pv(&self) -> bool {
+        self.fs_type() == Some(FsType::Lvm2Member)
+    }
+
+    /// Usage string: "filesystem", "raid", "crypto", "other"
+    pub fn usage(&self) -> Option<&str> {
+        self.get(SuperblockTag::Usage)
+    }
+
+    /// Filesystem label
+    pub fn label(&self) -> Option<&str> {
+        self.get(SuperblockTag::Label)
+    }
+
+    /// Filesystem UUID
+    pub fn uuid(&self) -> Option<&str> {
+        self.get(SuperblockTag::Uuid)
+    }
+
+    /// Filesystem UUID parsed to a comparable form, see [`VolumeId`]
+    pub fn volume_id(&self) -> Option<VolumeId> {
+        self.uuid().map(VolumeId::parse)
+    }
+
+    /// Filesystem version parsed to a comparable form, see [`FsVersion`]
+    pub fn fs_version(&self) -> Option<FsVersion> {
+        self.get(SuperblockTag::Version).map(FsVersion::parse)
+    }
+
+    /// Filesystem block size in bytes
+    pub fn block_size(&self) -> Option<u64> {
+        self.get(SuperblockTag::BlockSize)
+            .and_then(|size| size.parse().ok())
+    }
+
+    /// Subvolume or member device UUID (e.g. btrfs `dev_item.uuid`), unique per device even if
+    /// the filesystem UUID is shared
+    pub fn uuid_sub(&self) -> Option<&str> {
+        self.get(SuperblockTag::UuidSub)
+    }
+
+    /// UUID of the external log device (e.g. xfs)
+    pub fn log_uuid(&self) -> Option<&str> {
+        self.get(SuperblockTag::Loguuid)
+    }
+
+    /// UUID of the external journal device (e.g. ext3 or ext4)
+    pub fn ext_journal(&self) -> Option<&str> {
+        self.get(SuperblockTag::ExtJournal)
+    }
+
+    /// Partition table type
+    pub fn pt_type(&self) -> Option<PartitionTableType> {
+        self.get(PartitionTag::Pttype).map(|pt_type| {
+            PartitionTableType::from_str(pt_type)
+                .unwrap_or_else(|_| PartitionTableType::Unknown(pt_type.to_owned()))
+        })
+    }
+
+    /// Partition table ID (UUID for GPT, hex for DOS)
+    pub fn pt_uuid(&self) -> Option<&str> {
+        self.get(PartitionTag::Ptuuid)
+    }
+}
+
+impl From<Vec<Tag>> for ProbeResult {
+    fn from(tags: Vec<Tag>) -> Self {
+        Self { tags }
+    }
+}
+
+impl IntoIterator for ProbeResult {
+    type Item = Tag;
+    type IntoIter = std::vec::IntoIter<Tag>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.tags.into_iter()
+    }
+}
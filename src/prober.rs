@@ -1,18 +1,35 @@
 use crate::{
+    cancel::CancellationToken,
+    check_writable,
+    devno::Devno,
     error::{c_result, BlkIdError, BlkIdResult},
-    part_list::PartList,
-    path_to_cstring,
+    part_list::{PartList, PartListSnapshot},
+    part_table::{PartTableInfo, PartitionTableType},
+    probe_result::ProbeResult,
+    report::{FullReport, PartitionInfo, TopologyInfo},
+    tag::{Tag, TagType},
     topology::Topology,
-    PartitionsFlags, SuperblocksFlags,
+    OpenFlags, PartitionsFlags, SuperblocksFlags, UsageFlags,
 };
 use blkid_sys::*;
 use std::{
+    cell::RefCell,
     collections::HashMap,
     ffi::{CStr, CString},
-    path::Path,
+    fs::File,
+    mem::ManuallyDrop,
+    os::unix::{
+        fs::FileExt,
+        io::{AsRawFd, BorrowedFd, FromRawFd, OwnedFd},
+    },
+    path::{Path, PathBuf},
     ptr,
 };
 
+/// Name of the hint with the offset (in bytes) of the last session on multisession optical media,
+/// see [`Prober::set_hint`]
+pub const SESSION_OFFSET_HINT: &str = "session_offset";
+
 /// Low-level probing setting
 ///
 /// The probing routines are grouped together into separate chains. Currently, the library provides
@@ -31,9 +48,75 @@ use std::{
 ///
 /// The `superblocks` chain is enabled by default. The all others chains is necessary to enable by
 /// `enable_'CHAINNAME'()`.
-pub struct Prober(pub(crate) blkid_probe);
+pub struct Prober {
+    pub(crate) probe: blkid_probe,
+    config: RefCell<ProbeConfig>,
+    /// Descriptor duplicated by [`Self::try_clone`], closed after the probe is freed
+    owned_fd: Option<OwnedFd>,
+}
+
+/// Settings applied by the setters. `libblkid` does not provide getters for them, so they are
+/// tracked to be re-applied by [`Prober::try_clone`]. `None` means the `libblkid` default.
+#[derive(Clone, Debug, Default)]
+struct ProbeConfig {
+    superblocks: Option<bool>,
+    superblocks_flags: Option<SuperblocksFlags>,
+    partitions: Option<bool>,
+    partitions_flags: Option<PartitionsFlags>,
+    topology: Option<bool>,
+    sector_size: Option<u32>,
+}
+
+/// Probing chain, see [`Prober::enable_chain`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Chain {
+    /// Filesystems, RAID members, crypto containers etc. Enabled by default
+    Superblocks,
+    /// Partition tables
+    Partitions,
+    /// Device topology (sector and I/O sizes, alignment)
+    Topology,
+}
+
+impl Chain {
+    /// All chains
+    pub const ALL: [Chain; 3] = [Chain::Superblocks, Chain::Partitions, Chain::Topology];
+}
+
+/// Chain and flag combinations for common use cases, see [`Prober::preset`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Profile {
+    /// Superblocks chain only with the default values plus `USAGE` and `VERSION`
+    FilesystemOnly,
+    /// Partitions chain only with the partition entry details
+    PartitionsOnly,
+    /// All chains with all values, including raw label and UUID and the magic strings. Note
+    /// that the topology chain fails for regular files, see [`Prober::enable_all_chains`]
+    FullForensic,
+    /// Superblocks chain only with `TYPE` and `UUID` values
+    FastIdentify,
+}
+
+/// How the probing filters treat the listed types or usages
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FilterMode {
+    /// Probe only the listed ones (`BLKID_FLTR_ONLYIN`)
+    OnlyIn,
+    /// Probe all except the listed ones (`BLKID_FLTR_NOTIN`)
+    NotIn,
+}
+
+impl FilterMode {
+    fn as_raw(self) -> i32 {
+        match self {
+            Self::OnlyIn => BLKID_FLTR_ONLYIN as i32,
+            Self::NotIn => BLKID_FLTR_NOTIN as i32,
+        }
+    }
+}
 
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ProbeState {
     Success,
     Done,
@@ -43,7 +126,7 @@ pub enum ProbeState {
 
 impl Drop for Prober {
     fn drop(&mut self) {
-        unsafe { blkid_free_probe(self.0) }
+        unsafe { blkid_free_probe(self.probe) }
     }
 }
 
@@ -51,15 +134,106 @@ impl Prober {
     /// Create newly allocated `probe` struct.
     pub fn new() -> BlkIdResult<Self> {
         let probe = unsafe { c_result(blkid_new_probe()) }?;
-        Ok(Self(probe))
+        Ok(Self::from_raw(probe))
     }
 
     /// Create newly allocated `probe` struct by filename.
     /// `filename` can be either regular file or device
+    ///
+    /// The same as [`Self::open`] with no extra flags, so the device is opened with `O_NONBLOCK`
+    /// and a removable drive without medium fails with [`BlkIdError::NoMedium`] instead of
+    /// waiting for the medium.
     pub fn new_from_filename<P: AsRef<Path>>(filename: P) -> BlkIdResult<Self> {
-        let path = path_to_cstring(filename)?;
-        let probe = unsafe { c_result(blkid_new_probe_from_filename(path.as_ptr())) }?;
-        Ok(Self(probe))
+        Self::open(filename, OpenFlags::empty())
+    }
+
+    /// Opens the device (or image file) with the specified flags and assigns it to a new prober.
+    /// The descriptor is owned by the prober.
+    ///
+    /// For example [`OpenFlags::DIRECT`] bypasses the page cache. Note that `O_DIRECT` requires
+    /// aligned buffers and `libblkid` builds which do not align their read buffers fail with
+    /// `EINVAL` on such devices.
+    pub fn open<P: AsRef<Path>>(path: P, flags: OpenFlags) -> BlkIdResult<Self> {
+        let path = path.as_ref();
+        let file = flags.open(path)?;
+
+        let mut prober = Self::new()?;
+        prober
+            .assign_device(file, 0, None)
+            .map_err(|err| err.with_path(path))?;
+        Ok(prober)
+    }
+
+    /// Creates prober for an image which is already in memory (e.g. memory-mapped evidence image).
+    ///
+    /// `libblkid` reads by file descriptor only, so the data are copied to an anonymous memory
+    /// file (`memfd_create`) instead of a temporary file on disk. The memory file is owned by
+    /// the prober.
+    pub fn from_bytes<D: AsRef<[u8]>>(data: D) -> BlkIdResult<Self> {
+        let name = CString::new("blkid-image")?;
+        let fd = unsafe { libc::memfd_create(name.as_ptr(), libc::MFD_CLOEXEC) };
+        if fd < 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+        File::from(fd.try_clone()?).write_all_at(data.as_ref(), 0)?;
+
+        let mut prober = Self::new()?;
+        prober.assign_device(fd, 0, None)?;
+        Ok(prober)
+    }
+
+    fn from_raw(probe: blkid_probe) -> Self {
+        Self {
+            probe,
+            config: RefCell::new(ProbeConfig::default()),
+            owned_fd: None,
+        }
+    }
+
+    /// Creates a new prober for the same device. The file descriptor is duplicated (the clone
+    /// owns the duplicate) and the probing area, sector size, enabled chains and chain flags are
+    /// re-applied. Probing filters, hints and results are not copied.
+    ///
+    /// This allows to run several differently configured probing passes over the same device
+    /// without reopening it by path.
+    pub fn try_clone(&self) -> BlkIdResult<Self> {
+        let fd = self
+            .get_fd()
+            .ok_or_else(|| std::io::Error::from_raw_os_error(libc::EBADF))?
+            .try_clone_to_owned()?;
+
+        let mut clone = Self::new()?;
+        clone.set_device(fd.as_raw_fd(), self.get_offset()?, Some(self.get_size()?))?;
+        clone.owned_fd = Some(fd);
+
+        let config = self.config.borrow().clone();
+        clone.apply_config(&config)?;
+        Ok(clone)
+    }
+
+    /// Applies the settings tracked in `config`, `None` values are left untouched
+    fn apply_config(&self, config: &ProbeConfig) -> BlkIdResult<()> {
+        if let Some(enable) = config.superblocks {
+            self.enable_superblocks(enable)?;
+        }
+        if let Some(flags) = config.superblocks_flags {
+            self.set_superblocks_flags(flags)?;
+        }
+        if let Some(enable) = config.partitions {
+            self.enable_partitions(enable)?;
+        }
+        if let Some(flags) = config.partitions_flags {
+            self.set_partitions_flags(flags)?;
+        }
+        if let Some(enable) = config.topology {
+            self.enable_topology(enable)?;
+        }
+        #[cfg(blkid = "2.30")]
+        if let Some(size) = config.sector_size {
+            self.set_sector_size(size)?;
+        }
+        Ok(())
     }
 
     /// Calls probing functions in all enabled chains. The superblocks chain is enabled by default.
@@ -89,13 +263,15 @@ impl Prober {
     /// ```ignore, compile_fail
     /// let prober = Prober::new().unwrap();
     ///
-    /// while prober.do_probe() == ProbeState::Done {
+    /// while prober.do_probe() == ProbeState::Success {
     ///     let value_map = prober.get_values_map().unwrap();
     ///     println!("{:#?}", value_map);
     /// }
     /// ```
+    ///
+    /// See also [`Self::probe_all_signatures`].
     pub fn do_probe(&self) -> BlkIdResult<ProbeState> {
-        let ret_code = unsafe { blkid_do_probe(self.0) };
+        let ret_code = unsafe { blkid_do_probe(self.probe) };
 
         match ret_code {
             0 => Ok(ProbeState::Success),
@@ -104,6 +280,42 @@ impl Prober {
         }
     }
 
+    /// Calls [`Self::do_probe`] in a loop from the begin until [`ProbeState::Done`] and collects
+    /// the result of every detected signature (e.g. a filesystem and the partition table, or
+    /// more filesystems on the same device).
+    ///
+    /// Unlike [`Self::do_safe_probe`] it does not check for ambivalent results, an empty vector
+    /// means that nothing was detected.
+    pub fn probe_all_signatures(&self) -> BlkIdResult<Vec<ProbeResult>> {
+        let mut results = Vec::new();
+        self.reset();
+
+        while self.do_probe()? == ProbeState::Success {
+            results.push(self.result()?);
+        }
+
+        Ok(results)
+    }
+
+    /// Calls [`Self::do_probe`] in a loop from the begin and collects results of all probing
+    /// functions. The `token` is checked before every step, so probing of a slow device stops
+    /// after the current step when the token is cancelled.
+    pub fn probe_all_cancellable(
+        &self,
+        token: &CancellationToken,
+    ) -> BlkIdResult<Vec<ProbeResult>> {
+        let mut results = Vec::new();
+        self.reset();
+
+        loop {
+            token.check()?;
+            if self.do_probe()? != ProbeState::Success {
+                return Ok(results);
+            }
+            results.push(self.result()?);
+        }
+    }
+
     /// This function gathers probing results from all enabled chains and checks for ambivalent
     /// results (e.g. more filesystems on the device).
     ///
@@ -122,7 +334,7 @@ impl Prober {
     /// * [`ProberState::NothingDetected`]
     /// * [`ProberState::Ambivalent`]
     pub fn do_safe_probe(&self) -> BlkIdResult<ProbeState> {
-        let ret_code = unsafe { blkid_do_safeprobe(self.0) };
+        let ret_code = unsafe { blkid_do_safeprobe(self.probe) };
 
         match ret_code {
             0 => Ok(ProbeState::Success),
@@ -139,7 +351,7 @@ impl Prober {
     /// * [`ProberState::Success`]
     /// * [`ProberState::NothingDetected`]
     pub fn do_full_probe(&self) -> BlkIdResult<ProbeState> {
-        let ret_code = unsafe { blkid_do_safeprobe(self.0) };
+        let ret_code = unsafe { blkid_do_fullprobe(self.probe) };
 
         match ret_code {
             0 => Ok(ProbeState::Success),
@@ -175,7 +387,10 @@ impl Prober {
     /// }
     /// ```
     pub fn do_wipe(&self, dry_run: bool) -> BlkIdResult<ProbeState> {
-        let ret_code = unsafe { blkid_do_wipe(self.0, dry_run as i32) };
+        if !dry_run {
+            check_writable()?;
+        }
+        let ret_code = unsafe { blkid_do_wipe(self.probe, dry_run as i32) };
 
         match ret_code {
             0 => Ok(ProbeState::Success),
@@ -192,7 +407,7 @@ impl Prober {
 
         unsafe {
             c_result(blkid_probe_get_value(
-                self.0,
+                self.probe,
                 num,
                 &mut name_ptr,
                 &mut data_ptr,
@@ -218,10 +433,59 @@ impl Prober {
         Ok(map)
     }
 
+    /// Iterates over the probed values without collecting them first. The values are retrieved
+    /// lazily by `blkid_probe_get_value` and converted lossy (see [`Self::result`]).
+    ///
+    /// The iterator borrows the prober, the values are valid only until the next probing.
+    pub fn iter_values(&self) -> BlkIdResult<Values<'_>> {
+        Ok(Values {
+            prober: self,
+            next: 0,
+            len: self.numof_values()?,
+        })
+    }
+
+    /// Retrieve owned [`ProbeResult`] with all the probed values.
+    ///
+    /// Unlike [`Self::get_value`] this does not fail on binary values (e.g. `SBMAGIC`), such
+    /// values are converted lossy.
+    pub fn result(&self) -> BlkIdResult<ProbeResult> {
+        let numof_values = self.numof_values()?;
+        let mut tags = Vec::with_capacity(numof_values as usize);
+
+        for i in 0..numof_values {
+            let (name, data) = self.get_value_bytes(i)?;
+            let value = String::from_utf8_lossy(&data);
+            tags.push(Tag::new(TagType::from(name.as_str()), value.as_ref()));
+        }
+
+        Ok(ProbeResult::from(tags))
+    }
+
+    /// Same as [`Self::get_value`] but returns data as raw bytes without trailing `NUL`
+    pub(crate) fn get_value_bytes(&self, num: i32) -> BlkIdResult<(String, Vec<u8>)> {
+        let mut name_ptr: *const ::libc::c_char = ptr::null();
+        let mut data_ptr: *const ::libc::c_char = ptr::null();
+        let mut len = 0;
+
+        unsafe {
+            c_result(blkid_probe_get_value(
+                self.probe,
+                num,
+                &mut name_ptr,
+                &mut data_ptr,
+                &mut len,
+            ))
+        }?;
+
+        let name_value = unsafe { CStr::from_ptr(name_ptr).to_str()?.to_owned() };
+        Ok((name_value, unsafe { raw_data(data_ptr, len) }))
+    }
+
     /// Check if device has the specified value
     pub fn has_value(&self, name: &str) -> BlkIdResult<bool> {
         let name = CString::new(name)?;
-        unsafe { c_result(blkid_probe_has_value(self.0, name.as_ptr())).map(|val| val == 1) }
+        unsafe { c_result(blkid_probe_has_value(self.probe, name.as_ptr())).map(|val| val == 1) }
     }
 
     /// Value by specified `name`
@@ -230,12 +494,17 @@ impl Prober {
     ///
     /// You should call [`Self::do_probe`] before using this
     pub fn lookup_value(&self, name: &str) -> BlkIdResult<String> {
-        let name = CString::new(name)?;
+        self.lookup_value_cstr(&CString::new(name)?)
+    }
+
+    /// Same as [`Self::lookup_value`] without allocation of the name, see
+    /// [`crate::tag::cstr`] for the common names
+    pub fn lookup_value_cstr(&self, name: &CStr) -> BlkIdResult<String> {
         let mut data_ptr: *const ::libc::c_char = ptr::null();
         let mut len = 0;
         unsafe {
             c_result(blkid_probe_lookup_value(
-                self.0,
+                self.probe,
                 name.as_ptr(),
                 &mut data_ptr,
                 &mut len,
@@ -246,24 +515,123 @@ impl Prober {
         Ok(data_value)
     }
 
+    /// Looks up several values at once, missing values are `None`
+    ///
+    /// # Note
+    ///
+    /// You should call [`Self::do_probe`] before using this
+    pub fn lookup_values(
+        &self,
+        tag_types: &[TagType],
+    ) -> BlkIdResult<HashMap<TagType, Option<String>>> {
+        tag_types
+            .iter()
+            .map(|tag_type| {
+                let value = self
+                    .lookup_value_bytes(&tag_type.to_string())?
+                    .map(|value| String::from_utf8_lossy(&value).into_owned());
+                Ok((tag_type.clone(), value))
+            })
+            .collect()
+    }
+
+    /// Same as [`Self::lookup_values`] but all values are required. Returns
+    /// [`BlkIdError::MissingTags`] listing every missing value otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore, compile_fail
+    /// // The device must have a filesystem with UUID
+    /// let required = [SuperblockTag::Type.into(), SuperblockTag::Uuid.into()];
+    /// let values = prober.require_values(&required)?;
+    /// ```
+    pub fn require_values(&self, tag_types: &[TagType]) -> BlkIdResult<HashMap<TagType, String>> {
+        let mut values = HashMap::with_capacity(tag_types.len());
+        let mut missing = Vec::new();
+
+        for (tag_type, value) in self.lookup_values(tag_types)? {
+            match value {
+                Some(value) => {
+                    values.insert(tag_type, value);
+                }
+                None => missing.push(tag_type),
+            }
+        }
+
+        if missing.is_empty() {
+            Ok(values)
+        } else {
+            // Keep the order of the request
+            missing.sort_by_key(|tag_type| tag_types.iter().position(|t| t == tag_type));
+            Err(BlkIdError::MissingTags(missing))
+        }
+    }
+
+    /// Same as [`Self::lookup_value`] but returns data as raw bytes without trailing `NUL`, or
+    /// `None` if the value is not available
+    pub(crate) fn lookup_value_bytes(&self, name: &str) -> BlkIdResult<Option<Vec<u8>>> {
+        let name = CString::new(name)?;
+        let mut data_ptr: *const ::libc::c_char = ptr::null();
+        let mut len = 0;
+        let ret_code =
+            unsafe { blkid_probe_lookup_value(self.probe, name.as_ptr(), &mut data_ptr, &mut len) };
+
+        if ret_code == 0 {
+            Ok(Some(unsafe { raw_data(data_ptr, len) }))
+        } else {
+            Ok(None)
+        }
+    }
+
     /// Number of values in probing result
     pub fn numof_values(&self) -> BlkIdResult<i32> {
-        unsafe { c_result(blkid_probe_numof_values(self.0)) }
+        unsafe { c_result(blkid_probe_numof_values(self.probe)) }
     }
 
-    /// Block device number, or 0 for regular file
-    pub fn get_devno(&self) -> u64 {
-        unsafe { blkid_probe_get_devno(self.0) }
+    /// Block device number, `None` for regular file or if no device is assigned
+    pub fn get_devno(&self) -> Option<Devno> {
+        match unsafe { blkid_probe_get_devno(self.probe) } {
+            0 => None,
+            devno => Some(Devno::from_dev_t(devno)),
+        }
     }
 
-    /// File descriptor for assigned device/file
-    pub fn get_fd(&self) -> i32 {
-        unsafe { blkid_probe_get_fd(self.0) }
+    /// File descriptor for assigned device/file, `None` if no device is assigned
+    pub fn get_fd(&self) -> Option<BorrowedFd<'_>> {
+        match unsafe { blkid_probe_get_fd(self.probe) } {
+            fd if fd < 0 => None,
+            fd => Some(unsafe { BorrowedFd::borrow_raw(fd) }),
+        }
+    }
+
+    /// Reads exactly `buf.len()` bytes from the assigned device at absolute `offset` (not relative
+    /// to the probing area)
+    pub(crate) fn read_exact_at(&self, buf: &mut [u8], offset: u64) -> BlkIdResult<()> {
+        Ok(self.borrow_file()?.read_exact_at(buf, offset)?)
+    }
+
+    /// Writes whole `buf` to the assigned device at absolute `offset` (not relative to the probing
+    /// area) and flushes it to the disk
+    pub(crate) fn write_all_at(&self, buf: &[u8], offset: u64) -> BlkIdResult<()> {
+        check_writable()?;
+        let file = self.borrow_file()?;
+        file.write_all_at(buf, offset)?;
+        Ok(file.sync_all()?)
+    }
+
+    /// The descriptor is owned by `libblkid` or by the caller of [`Self::set_device`], so it must
+    /// not be closed here
+    fn borrow_file(&self) -> BlkIdResult<ManuallyDrop<File>> {
+        let fd = self
+            .get_fd()
+            .ok_or_else(|| std::io::Error::from_raw_os_error(libc::EBADF))?;
+        let file = unsafe { File::from_raw_fd(fd.as_raw_fd()) };
+        Ok(ManuallyDrop::new(file))
     }
 
     /// Block device logical sector size (`BLKSSZGET` ioctl, default 512)
     pub fn get_sector_size(&self) -> u32 {
-        unsafe { blkid_probe_get_sectorsize(self.0) }
+        unsafe { blkid_probe_get_sectorsize(self.probe) }
     }
 
     /// Set logical sector size.
@@ -272,33 +640,44 @@ impl Prober {
     /// before any probing call.
     #[cfg(blkid = "2.30")]
     pub fn set_sector_size(&self, size: u32) -> BlkIdResult<()> {
-        unsafe { c_result(blkid_probe_set_sectorsize(self.0, size)).map(|_| ()) }
+        unsafe { c_result(blkid_probe_set_sectorsize(self.probe, size)) }?;
+        self.config.borrow_mut().sector_size = Some(size);
+        Ok(())
     }
 
     /// 512-byte sector count
     pub fn get_sectors(&self) -> BlkIdResult<i64> {
-        unsafe { c_result(blkid_probe_get_sectors(self.0)) }
+        unsafe { c_result(blkid_probe_get_sectors(self.probe)) }
     }
 
     /// Size of probing area in bytes as defined by [`Self::set_device`]. If the size of the probing
     /// area is unrestricted then this function returns the real size of device
     pub fn get_size(&self) -> BlkIdResult<i64> {
-        unsafe { c_result(blkid_probe_get_size(self.0)) }
+        unsafe { c_result(blkid_probe_get_size(self.probe)) }
     }
 
     /// Offset of probing area as defined by [`Self::set_device`]
     pub fn get_offset(&self) -> BlkIdResult<i64> {
-        unsafe { c_result(blkid_probe_get_offset(self.0)) }
+        unsafe { c_result(blkid_probe_get_offset(self.probe)) }
     }
 
-    /// Device number of the wholedisk, or 0 for regular files
-    pub fn get_wholedisk_devno(&self) -> u64 {
-        unsafe { blkid_probe_get_wholedisk_devno(self.0) }
+    /// Device number of the wholedisk, `None` for regular files
+    pub fn get_wholedisk_devno(&self) -> Option<Devno> {
+        match unsafe { blkid_probe_get_wholedisk_devno(self.probe) } {
+            0 => None,
+            devno => Some(Devno::from_dev_t(devno)),
+        }
+    }
+
+    /// Device node of the wholedisk (e.g. `/dev/sda` for `/dev/sda3`), `None` for regular files or
+    /// if the node cannot be found
+    pub fn wholedisk_path(&self) -> Option<PathBuf> {
+        self.get_wholedisk_devno()?.to_devname()
     }
 
     /// If device is wholedisk
     pub fn is_wholedisk(&self) -> bool {
-        unsafe { blkid_probe_is_wholedisk(self.0) == 1 }
+        unsafe { blkid_probe_is_wholedisk(self.probe) == 1 }
     }
 
     /// Modifies in-memory cached data from the device. The specified range is zeroized.
@@ -311,7 +690,7 @@ impl Prober {
     /// The [`Self::reset_buffers`] reverts all.
     #[cfg(blkid = "2.31")]
     pub fn hide_range(&self, offset: u64, size: u64) -> BlkIdResult<()> {
-        unsafe { c_result(blkid_probe_hide_range(self.0, offset, size)).map(|_| ()) }
+        unsafe { c_result(blkid_probe_hide_range(self.probe, offset, size)).map(|_| ()) }
     }
 
     /// Reuse all already read buffers from the device. The buffers may be modified by
@@ -319,7 +698,7 @@ impl Prober {
     /// will read all data from the device.
     #[cfg(blkid = "2.31")]
     pub fn reset_buffers(&self) -> BlkIdResult<()> {
-        unsafe { c_result(blkid_probe_reset_buffers(self.0)).map(|_| ()) }
+        unsafe { c_result(blkid_probe_reset_buffers(self.probe)).map(|_| ()) }
     }
 
     /// This function move pointer to the probing chain one step back - it means that the
@@ -339,7 +718,7 @@ impl Prober {
     /// ```
     #[cfg(blkid = "2.23")]
     pub fn step_back(&self) -> BlkIdResult<()> {
-        unsafe { c_result(blkid_probe_step_back(self.0)).map(|_| ()) }
+        unsafe { c_result(blkid_probe_step_back(self.probe)).map(|_| ()) }
     }
 
     /// Assigns the device to probe control struct, resets internal buffers and resets the current
@@ -348,20 +727,153 @@ impl Prober {
     /// `fd`: device file descriptor
     /// `offset`: begin of probing area
     /// `size`: size of probing area (`None` means whole device/file)
+    ///
+    /// Returns [`BlkIdError::NotSeekable`] for pipes and sockets and [`BlkIdError::EmptyInput`]
+    /// if there is no data behind `offset`.
+    ///
+    /// Requires exclusive access, so no [`PartList`] or [`Topology`] of the previous device can be
+    /// used afterwards.
+    ///
+    /// The caller has to keep `fd` open while the prober uses it, see [`Self::assign_device`]
+    /// for the owning variant.
     pub fn set_device(&mut self, fd: i32, offset: i64, size: Option<i64>) -> BlkIdResult<()> {
+        check_input(fd, offset)?;
         let size = size.unwrap_or(0);
-        unsafe { c_result(blkid_probe_set_device(self.0, fd, offset, size)).map(|_| ()) }
+        unsafe { c_result(blkid_probe_set_device(self.probe, fd, offset, size)) }?;
+        self.config.borrow_mut().sector_size = None;
+        Ok(())
+    }
+
+    /// Same as [`Self::set_device`] but takes ownership of the descriptor (e.g. [`File`] or
+    /// [`OwnedFd`]), so it stays open as long as the prober uses it. The descriptor is closed
+    /// when another device is assigned by this function or when the prober is dropped.
+    pub fn assign_device<F: Into<OwnedFd>>(
+        &mut self,
+        fd: F,
+        offset: i64,
+        size: Option<i64>,
+    ) -> BlkIdResult<()> {
+        let fd = fd.into();
+        self.set_device(fd.as_raw_fd(), offset, size)?;
+        self.owned_fd = Some(fd);
+        Ok(())
+    }
+
+    /// Reassigns the current device restricted to the region of `size` bytes at `offset` and runs
+    /// [`Self::do_safe_probe`] there. Useful to probe filesystems inside logical volumes,
+    /// partitions of images or blobs found in the middle of a disk.
+    ///
+    /// The descriptor is duplicated, so it works for probers created by path too. The prober
+    /// stays restricted to the region, the offsets in the result are relative to the region.
+    /// Returns empty result if nothing was detected.
+    pub fn probe_region(&mut self, offset: i64, size: i64) -> BlkIdResult<ProbeResult> {
+        let fd = self
+            .get_fd()
+            .ok_or_else(|| std::io::Error::from_raw_os_error(libc::EBADF))?
+            .try_clone_to_owned()?;
+        self.assign_device(fd, offset, Some(size))?;
+
+        match self.do_safe_probe()? {
+            ProbeState::Success => self.result(),
+            _ => Ok(ProbeResult::default()),
+        }
     }
 
     /// Zeroize probing results and resets the current probing (this has impact to [`Self::do_probe`]
     /// only). This function does not touch probing filters and keeps assigned device.
-    pub fn reset_probe(&self) {
-        unsafe { blkid_reset_probe(self.0) }
+    ///
+    /// Requires exclusive access, so no [`PartList`] or [`Topology`] returned before can be used
+    /// afterwards.
+    pub fn reset_probe(&mut self) {
+        self.reset();
+    }
+
+    /// [`Self::reset_probe`] for the crate internal probing loops. No [`PartList`] or
+    /// [`Topology`] can be alive while the prober is borrowed shared, they borrow it mutably.
+    pub(crate) fn reset(&self) {
+        unsafe { blkid_reset_probe(self.probe) }
+    }
+
+    /// Enables/disables the `chain` for non-binary interface
+    pub fn enable_chain(&self, chain: Chain, enable: bool) -> BlkIdResult<()> {
+        match chain {
+            Chain::Superblocks => self.enable_superblocks(enable),
+            Chain::Partitions => self.enable_partitions(enable),
+            Chain::Topology => self.enable_topology(enable),
+        }
+    }
+
+    /// Returns the chains enabled for the non-binary interface (the superblocks chain unless
+    /// disabled, other chains if enabled by the setters), in the order they run
+    pub fn enabled_chains(&self) -> Vec<Chain> {
+        let config = self.config.borrow();
+        Chain::ALL
+            .into_iter()
+            .filter(|chain| match chain {
+                Chain::Superblocks => config.superblocks.unwrap_or(true),
+                Chain::Partitions => config.partitions.unwrap_or(false),
+                Chain::Topology => config.topology.unwrap_or(false),
+            })
+            .collect()
+    }
+
+    /// Enables all chains. Note that the topology chain fails for regular files, so
+    /// [`Self::do_safe_probe`] of an image fails with all chains enabled.
+    pub fn enable_all_chains(&self) -> BlkIdResult<()> {
+        Chain::ALL
+            .iter()
+            .try_for_each(|chain| self.enable_chain(*chain, true))
+    }
+
+    /// Disables all chains, including the superblocks chain which is enabled by default
+    pub fn disable_all_chains(&self) -> BlkIdResult<()> {
+        Chain::ALL
+            .iter()
+            .try_for_each(|chain| self.enable_chain(*chain, false))
+    }
+
+    /// Enables `chain` and disables the others, e.g.
+    /// `Prober::new_from_filename(path)?.with_only(Chain::Partitions)?` probes partition tables
+    /// only
+    pub fn with_only(self, chain: Chain) -> BlkIdResult<Self> {
+        for other in Chain::ALL {
+            self.enable_chain(other, other == chain)?;
+        }
+        Ok(self)
+    }
+
+    /// Enables chains and sets flags of the `profile`. Chains which are not part of the profile
+    /// are disabled
+    pub fn preset(&self, profile: Profile) -> BlkIdResult<()> {
+        let chain = match profile {
+            Profile::FilesystemOnly | Profile::FastIdentify => Some(Chain::Superblocks),
+            Profile::PartitionsOnly => Some(Chain::Partitions),
+            Profile::FullForensic => None,
+        };
+        for other in Chain::ALL {
+            self.enable_chain(other, chain.is_none() || chain == Some(other))?;
+        }
+
+        match profile {
+            Profile::FilesystemOnly => self.set_superblocks_flags(
+                SuperblocksFlags::DEFAULT | SuperblocksFlags::USAGE | SuperblocksFlags::VERSION,
+            ),
+            Profile::PartitionsOnly => self.set_partitions_flags(PartitionsFlags::ENTRY_DETAILS),
+            Profile::FullForensic => {
+                self.set_superblocks_flags(SuperblocksFlags::all())?;
+                self.set_partitions_flags(PartitionsFlags::ENTRY_DETAILS | PartitionsFlags::MAGIC)
+            }
+            Profile::FastIdentify => {
+                self.set_superblocks_flags(SuperblocksFlags::TYPE | SuperblocksFlags::UUID)
+            }
+        }
     }
 
     /// Enables/disables the superblocks probing for non-binary interface.
     pub fn enable_superblocks(&self, enable: bool) -> BlkIdResult<()> {
-        unsafe { c_result(blkid_probe_enable_superblocks(self.0, enable as i32)).map(|_| ()) }
+        unsafe { c_result(blkid_probe_enable_superblocks(self.probe, enable as i32)) }?;
+        self.config.borrow_mut().superblocks = Some(enable);
+        Ok(())
     }
 
     /// If known filesystem type
@@ -370,52 +882,128 @@ impl Prober {
         Ok(unsafe { blkid_known_fstype(fstype.as_ptr()) == 1 })
     }
 
-    // TODO: implement
-    // pub fn superblocks_get_name() {}
+    /// Returns name and usage of a supported superblock (filesystem, RAID, etc.), fails when
+    /// `idx` is out of range
+    pub fn superblocks_get_name(idx: usize) -> BlkIdResult<(String, UsageFlags)> {
+        let mut name: *const ::libc::c_char = ptr::null();
+        let mut usage = 0;
+        unsafe { c_result(blkid_superblocks_get_name(idx, &mut name, &mut usage)) }?;
+        let name = unsafe { CStr::from_ptr(name).to_str()?.to_owned() };
+        Ok((name, UsageFlags::from_bits_truncate(usage)))
+    }
+
+    /// Returns all superblocks (filesystems, RAIDs, etc.) the linked `libblkid` can detect
+    pub fn supported_filesystems() -> Vec<(String, UsageFlags)> {
+        (0..)
+            .map_while(|idx| Self::superblocks_get_name(idx).ok())
+            .collect()
+    }
 
-    // TODO: implement
-    // pub fn filter_superblocks_type() {}
+    /// Probes only (or all except) the filesystems and other superblocks of the listed types
+    /// (e.g. `&["ext4", "xfs"]`), see [`Self::known_fstype`]. The filter replaces the previous
+    /// one and resets the probing.
+    pub fn filter_superblocks_type<S: AsRef<str>>(
+        &self,
+        mode: FilterMode,
+        names: &[S],
+    ) -> BlkIdResult<()> {
+        let mut names = FilterNames::new(names)?;
+        unsafe {
+            c_result(blkid_probe_filter_superblocks_type(
+                self.probe,
+                mode.as_raw(),
+                names.as_mut_ptr(),
+            ))
+        }?;
+        Ok(())
+    }
 
-    // TODO: implement
-    // pub fn filter_superblocks_usage() {}
+    /// Probes only (or all except) the superblocks with the usages, e.g. only RAID members. The
+    /// filter replaces the previous one and resets the probing.
+    pub fn filter_superblocks_usage(&self, mode: FilterMode, usage: UsageFlags) -> BlkIdResult<()> {
+        unsafe {
+            c_result(blkid_probe_filter_superblocks_usage(
+                self.probe,
+                mode.as_raw(),
+                usage.bits(),
+            ))
+        }?;
+        Ok(())
+    }
 
     /// Inverts superblocks probing filter
     pub fn invert_superblocks_filter(&self) -> BlkIdResult<()> {
-        unsafe { c_result(blkid_probe_invert_superblocks_filter(self.0)).map(|_| ()) }
+        unsafe { c_result(blkid_probe_invert_superblocks_filter(self.probe)).map(|_| ()) }
     }
 
     /// Resets superblocks probing filter
     pub fn reset_superblocks_filter(&self) -> BlkIdResult<()> {
-        unsafe { c_result(blkid_probe_reset_superblocks_filter(self.0)).map(|_| ()) }
+        unsafe { c_result(blkid_probe_reset_superblocks_filter(self.probe)).map(|_| ()) }
     }
 
     /// Sets probing flags to the superblocks prober. This function is optional, the default are
     /// [`Superblocks::DEFAULT`] flags.
     pub fn set_superblocks_flags(&self, flags: SuperblocksFlags) -> BlkIdResult<()> {
-        unsafe { c_result(blkid_probe_set_superblocks_flags(self.0, flags.bits())).map(|_| ()) }
+        unsafe { c_result(blkid_probe_set_superblocks_flags(self.probe, flags.bits())) }?;
+        self.config.borrow_mut().superblocks_flags = Some(flags);
+        Ok(())
     }
 
     /// Enables/disables the partitions probing for non-binary interface
     pub fn enable_partitions(&self, enable: bool) -> BlkIdResult<()> {
-        unsafe { c_result(blkid_probe_enable_partitions(self.0, enable as i32)).map(|_| ()) }
+        unsafe { c_result(blkid_probe_enable_partitions(self.probe, enable as i32)) }?;
+        self.config.borrow_mut().partitions = Some(enable);
+        Ok(())
     }
 
     /// Sets probing flags to the partitions prober. This function is optional
     pub fn set_partitions_flags(&self, flags: PartitionsFlags) -> BlkIdResult<()> {
-        unsafe { c_result(blkid_probe_set_partitions_flags(self.0, flags.bits())).map(|_| ()) }
+        unsafe { c_result(blkid_probe_set_partitions_flags(self.probe, flags.bits())) }?;
+        self.config.borrow_mut().partitions_flags = Some(flags);
+        Ok(())
+    }
+
+    /// Sets or clears [`PartitionsFlags::FORCE_GPT`], keeping the other partitions flags set by
+    /// [`Self::set_partitions_flags`].
+    ///
+    /// With the flag the GPT is read even if the protective MBR is missing or damaged.
+    pub fn force_gpt(&self, enable: bool) -> BlkIdResult<()> {
+        let mut flags = self
+            .config
+            .borrow()
+            .partitions_flags
+            .unwrap_or_else(PartitionsFlags::empty);
+        flags.set(PartitionsFlags::FORCE_GPT, enable);
+        self.set_partitions_flags(flags)
     }
 
-    // TODO: implement
-    // pub fn filter_partitions_type() {}
+    /// Probes only (or all except) the partition tables of the listed types, e.g.
+    /// `&[PartitionTableType::Gpt, PartitionTableType::Dos]` or `&["gpt", "dos"]`. The filter
+    /// replaces the previous one and resets the probing.
+    pub fn filter_partitions_type<S: AsRef<str>>(
+        &self,
+        mode: FilterMode,
+        names: &[S],
+    ) -> BlkIdResult<()> {
+        let mut names = FilterNames::new(names)?;
+        unsafe {
+            c_result(blkid_probe_filter_partitions_type(
+                self.probe,
+                mode.as_raw(),
+                names.as_mut_ptr(),
+            ))
+        }?;
+        Ok(())
+    }
 
     /// Inverts partitions probing filter
     pub fn invert_partitions_filter(&self) -> BlkIdResult<()> {
-        unsafe { c_result(blkid_probe_invert_partitions_filter(self.0)).map(|_| ()) }
+        unsafe { c_result(blkid_probe_invert_partitions_filter(self.probe)).map(|_| ()) }
     }
 
     /// Resets partitions probing filter
     pub fn reset_partitions_filter(&self) -> BlkIdResult<()> {
-        unsafe { c_result(blkid_probe_reset_partitions_filter(self.0)).map(|_| ()) }
+        unsafe { c_result(blkid_probe_reset_partitions_filter(self.probe)).map(|_| ()) }
     }
 
     /// If known partition table type
@@ -428,11 +1016,19 @@ impl Prober {
     #[cfg(blkid = "2.30")]
     pub fn partitions_get_name(idx: usize) -> BlkIdResult<String> {
         let mut name: *const ::libc::c_char = ptr::null();
-        unsafe { c_result(blkid_partitions_get_name(idx.try_into().unwrap(), &mut name)) }?;
+        unsafe { c_result(blkid_partitions_get_name(idx, &mut name)) }?;
         let name = unsafe { CStr::from_ptr(name).to_str()?.to_owned() };
         Ok(name)
     }
 
+    /// Returns all partition table types the linked `libblkid` can detect
+    #[cfg(blkid = "2.30")]
+    pub fn supported_partition_tables() -> Vec<String> {
+        (0..)
+            .map_while(|idx| Self::partitions_get_name(idx).ok())
+            .collect()
+    }
+
     /// Returns [`PartList`] object.
     ///
     /// This is a binary interface for partitions.
@@ -443,14 +1039,95 @@ impl Prober {
     ///
     /// The returned object will be overwritten by the next [`Self::part_list`] call for the same
     /// prober. If you want to use more [`PartList`] objects in the same time you have to create
-    /// more [`Prober`] handlers.
-    pub fn part_list(&self) -> BlkIdResult<PartList> {
-        unsafe { c_result(blkid_probe_get_partitions(self.0)).map(PartList) }
+    /// more [`Prober`] handlers or owned copies by [`Self::partitions_snapshot`].
+    ///
+    /// The list borrows the prober mutably, because every probing call (e.g. [`Self::do_probe`])
+    /// frees the partition tables of the previous probing.
+    pub fn part_list(&mut self) -> BlkIdResult<PartList<'_>> {
+        self.part_list_unchecked()
+    }
+
+    /// [`Self::part_list`] for the crate internal functions which return owned data only, the
+    /// list must not outlive the caller
+    fn part_list_unchecked(&self) -> BlkIdResult<PartList<'_>> {
+        unsafe { c_result(blkid_probe_get_partitions(self.probe)).map(PartList::new) }
+    }
+
+    /// Returns owned report about the partition table, `None` if there is no partition table.
+    ///
+    /// In addition to [`crate::part_table::PartTable`] getters the report describes the MBR in
+    /// front of GPT, see [`Self::protective_mbr`].
+    pub fn part_table_info(&self) -> BlkIdResult<Option<PartTableInfo>> {
+        // libblkid returns NULL without errno if the partitions chain detects nothing
+        let table = match self.part_list_unchecked() {
+            Ok(list) => list.get_table(),
+            Err(BlkIdError::Io(err)) if err.raw_os_error() == Some(0) => None,
+            Err(err) => return Err(err),
+        };
+        let table = match table {
+            Some(table) => table,
+            None => return Ok(None),
+        };
+
+        let mut info = table.to_info()?;
+        if info.typ == Some(PartitionTableType::Gpt) {
+            info.protective_mbr = Some(self.protective_mbr()?);
+        }
+        Ok(Some(info))
+    }
+
+    /// Returns owned copy of the partition table and its partitions. The snapshot does not borrow
+    /// the prober, so it is not affected by the next [`Self::part_list`] call. The partitions are
+    /// read again, so it cannot be called while a [`PartList`] of the prober is alive.
+    ///
+    /// Like [`Self::part_list`] it is independent of the enabled chains.
+    pub fn partitions_snapshot(&self) -> BlkIdResult<PartListSnapshot> {
+        let table = self.part_table_info()?;
+        let partitions = match table {
+            Some(_) => self
+                .part_list_unchecked()?
+                .get_partitions()?
+                .iter()
+                .map(PartitionInfo::new)
+                .collect::<BlkIdResult<_>>()?,
+            None => Vec::new(),
+        };
+
+        Ok(PartListSnapshot { table, partitions })
+    }
+
+    /// Runs [`Self::do_safe_probe`] and collects its `NAME=value` results together with the
+    /// partition table, partitions and topology from the binary interfaces.
+    ///
+    /// If the device is a partition, the `PART_ENTRY_*` values are available as
+    /// [`FullReport::entry`] in the same form as the partitions of the device.
+    pub fn full_report(&self) -> BlkIdResult<FullReport> {
+        let state = self.do_safe_probe()?;
+        let values = match state {
+            ProbeState::Success => self.result()?,
+            _ => ProbeResult::default(),
+        };
+
+        let PartListSnapshot { table, partitions } = self.partitions_snapshot()?;
+        let topology = self
+            .topology_unchecked()
+            .ok()
+            .map(|topology| TopologyInfo::new(&topology));
+
+        Ok(FullReport {
+            state,
+            values,
+            table,
+            partitions,
+            topology,
+        })
     }
 
     /// Enables/disables the topology probing for non-binary interface
     pub fn enable_topology(&self, enable: bool) -> BlkIdResult<()> {
-        unsafe { c_result(blkid_probe_enable_topology(self.0, enable as i32)).map(|_| ()) }
+        unsafe { c_result(blkid_probe_enable_topology(self.probe, enable as i32)) }?;
+        self.config.borrow_mut().topology = Some(enable);
+        Ok(())
     }
 
     /// Returns topology.
@@ -464,8 +1141,17 @@ impl Prober {
     /// The returned object will be overwritten by the next [`Self::topology`] call for the same
     /// prober. If you want to use more [`Topology`] objects in the same time you have to create
     /// more [`Prober`] handlers.
-    pub fn topology(&self) -> BlkIdResult<Topology> {
-        unsafe { c_result(blkid_probe_get_topology(self.0)).map(Topology) }
+    ///
+    /// The topology borrows the prober mutably, because every probing call (e.g.
+    /// [`Self::do_probe`]) resets the topology of the previous probing.
+    pub fn topology(&mut self) -> BlkIdResult<Topology<'_>> {
+        self.topology_unchecked()
+    }
+
+    /// [`Self::topology`] for the crate internal functions which return owned data only, the
+    /// topology must not outlive the caller
+    fn topology_unchecked(&self) -> BlkIdResult<Topology<'_>> {
+        unsafe { c_result(blkid_probe_get_topology(self.probe)).map(Topology::new) }
     }
 
     /// Sets extra hint for low-level prober. If the hint is set by NAME=value notation than value
@@ -476,12 +1162,434 @@ impl Prober {
     #[cfg(blkid = "2.37")]
     pub fn set_hint(&self, hint_name: &str, offset: u64) -> BlkIdResult<()> {
         let name = CString::new(hint_name)?;
-        unsafe { c_result(blkid_probe_set_hint(self.0, name.as_ptr(), offset)).map(|_| ()) }
+        unsafe { c_result(blkid_probe_set_hint(self.probe, name.as_ptr(), offset)).map(|_| ()) }
+    }
+
+    /// Probes the session of multisession optical media (CD/DVD) which starts at `session_start`
+    /// bytes, e.g. the last session reported by the `CDROMMULTISESSION` ioctl. Without the hint
+    /// only the first session is visible.
+    ///
+    /// Returns the superblocks chain result of the session, empty if nothing was detected.
+    #[cfg(blkid = "2.37")]
+    pub fn probe_cd_session<P: AsRef<Path>>(
+        path: P,
+        session_start: u64,
+    ) -> BlkIdResult<ProbeResult> {
+        let prober = Self::new_from_filename(path)?;
+        prober.set_hint(SESSION_OFFSET_HINT, session_start)?;
+
+        match prober.do_safe_probe()? {
+            ProbeState::Success => prober.result(),
+            _ => Ok(ProbeResult::default()),
+        }
     }
 
     /// Removes all previously defined probing hints. See also [`Self::set_hint`]
     #[cfg(blkid = "2.37")]
     pub fn reset_hints(&self) {
-        unsafe { blkid_probe_reset_hints(self.0) }
+        unsafe { blkid_probe_reset_hints(self.probe) }
+    }
+}
+
+/// Configuration of a new [`Prober`], applied in the right order when the device is assigned
+///
+/// ```ignore, compile_fail
+/// let result = ProberBuilder::new()
+///     .superblocks(SuperblocksFlags::DEFAULT | SuperblocksFlags::USAGE)
+///     .partitions(PartitionsFlags::ENTRY_DETAILS)
+///     .filter_fs_types(FilterMode::OnlyIn, &["ext4", "xfs"])
+///     .probe("/dev/sda")?;
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ProberBuilder {
+    config: ProbeConfig,
+    fs_types: Option<(FilterMode, Vec<String>)>,
+    offset: i64,
+    size: Option<i64>,
+    open_flags: OpenFlags,
+}
+
+impl ProberBuilder {
+    /// Creates builder with the `libblkid` defaults (superblocks chain only, whole device)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enables the superblocks chain with `flags`, see [`Prober::set_superblocks_flags`]
+    pub fn superblocks(mut self, flags: SuperblocksFlags) -> Self {
+        self.config.superblocks = Some(true);
+        self.config.superblocks_flags = Some(flags);
+        self
+    }
+
+    /// Enables the partitions chain with `flags`, see [`Prober::set_partitions_flags`]
+    pub fn partitions(mut self, flags: PartitionsFlags) -> Self {
+        self.config.partitions = Some(true);
+        self.config.partitions_flags = Some(flags);
+        self
+    }
+
+    /// Enables/disables the topology chain. Note that the chain fails for regular files
+    pub fn topology(mut self, enable: bool) -> Self {
+        self.config.topology = Some(enable);
+        self
+    }
+
+    /// Enables/disables the `chain`, e.g. to disable the superblocks chain enabled by default
+    pub fn chain(mut self, chain: Chain, enable: bool) -> Self {
+        match chain {
+            Chain::Superblocks => self.config.superblocks = Some(enable),
+            Chain::Partitions => self.config.partitions = Some(enable),
+            Chain::Topology => self.config.topology = Some(enable),
+        }
+        self
+    }
+
+    /// Probes only (or all except) the listed filesystem types, see
+    /// [`Prober::filter_superblocks_type`]
+    pub fn filter_fs_types<S: AsRef<str>>(mut self, mode: FilterMode, names: &[S]) -> Self {
+        let names = names.iter().map(|name| name.as_ref().to_owned()).collect();
+        self.fs_types = Some((mode, names));
+        self
+    }
+
+    /// Begin of the probing area in bytes
+    pub fn offset(mut self, offset: i64) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Size of the probing area in bytes, the rest of the device by default
+    pub fn size(mut self, size: i64) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    /// Logical sector size, see [`Prober::set_sector_size`]
+    #[cfg(blkid = "2.30")]
+    pub fn sector_size(mut self, size: u32) -> Self {
+        self.config.sector_size = Some(size);
+        self
+    }
+
+    /// Flags used by [`Self::open`] and [`Self::probe`], e.g. [`OpenFlags::READ_WRITE`]
+    pub fn open_flags(mut self, flags: OpenFlags) -> Self {
+        self.open_flags = flags;
+        self
+    }
+
+    /// Returns flags used to open devices
+    pub fn get_open_flags(&self) -> OpenFlags {
+        self.open_flags
+    }
+
+    /// Opens the device (or image file) and returns the configured prober owning the descriptor
+    pub fn open<P: AsRef<Path>>(&self, path: P) -> BlkIdResult<Prober> {
+        let path = path.as_ref();
+        self.assign(self.open_flags.open(path)?)
+            .map_err(|err| err.with_path(path))
+    }
+
+    /// Returns the configured prober for an already open device, see [`Prober::assign_device`]
+    pub fn assign<F: Into<OwnedFd>>(&self, fd: F) -> BlkIdResult<Prober> {
+        let mut prober = Prober::new()?;
+        prober.assign_device(fd, self.offset, self.size)?;
+        prober.apply_config(&self.config)?;
+        if let Some((mode, names)) = &self.fs_types {
+            prober.filter_superblocks_type(*mode, names)?;
+        }
+        Ok(prober)
+    }
+
+    /// Opens the device and runs [`Prober::do_safe_probe`], the result is empty if nothing was
+    /// detected or the result is ambivalent
+    pub fn probe<P: AsRef<Path>>(&self, path: P) -> BlkIdResult<ProbeResult> {
+        let prober = self.open(path)?;
+        match prober.do_safe_probe()? {
+            ProbeState::Success => prober.result(),
+            _ => Ok(ProbeResult::default()),
+        }
+    }
+}
+
+/// Iterator over the probed values returned by [`Prober::iter_values`]
+pub struct Values<'a> {
+    prober: &'a Prober,
+    next: i32,
+    len: i32,
+}
+
+impl Iterator for Values<'_> {
+    type Item = (TagType, String);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next >= self.len {
+            return None;
+        }
+
+        match self.prober.get_value_bytes(self.next) {
+            Ok((name, data)) => {
+                self.next += 1;
+                Some((
+                    TagType::from(name.as_str()),
+                    String::from_utf8_lossy(&data).into_owned(),
+                ))
+            }
+            Err(_) => {
+                self.next = self.len;
+                None
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.len - self.next).max(0) as usize;
+        (0, Some(remaining))
+    }
+}
+
+/// `NULL` terminated array of names for the type filters
+struct FilterNames {
+    /// Owns the strings referenced by `ptrs`
+    _names: Vec<CString>,
+    ptrs: Vec<*mut libc::c_char>,
+}
+
+impl FilterNames {
+    fn new<S: AsRef<str>>(names: &[S]) -> BlkIdResult<Self> {
+        let names = names
+            .iter()
+            .map(|name| CString::new(name.as_ref()))
+            .collect::<Result<Vec<_>, _>>()?;
+        // libblkid does not modify the names, the pointers are mutable only in its signature
+        let ptrs = names
+            .iter()
+            .map(|name| name.as_ptr() as *mut libc::c_char)
+            .chain(std::iter::once(ptr::null_mut()))
+            .collect();
+        Ok(Self {
+            _names: names,
+            ptrs,
+        })
+    }
+
+    fn as_mut_ptr(&mut self) -> *mut *mut libc::c_char {
+        self.ptrs.as_mut_ptr()
+    }
+}
+
+/// Checks that `fd` supports random access and has some data behind `offset`. The current file
+/// offset is preserved.
+fn check_input(fd: i32, offset: i64) -> BlkIdResult<()> {
+    let current = unsafe { libc::lseek64(fd, 0, libc::SEEK_CUR) };
+    if current < 0 {
+        let err = std::io::Error::last_os_error();
+        return match err.raw_os_error() {
+            Some(libc::ESPIPE) => Err(BlkIdError::NotSeekable),
+            _ => Err(err.into()),
+        };
+    }
+
+    let end = unsafe { libc::lseek64(fd, 0, libc::SEEK_END) };
+    unsafe { libc::lseek64(fd, current, libc::SEEK_SET) };
+    if end < 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+
+    if end <= offset {
+        Err(BlkIdError::EmptyInput)
+    } else {
+        Ok(())
+    }
+}
+
+/// Copies value data returned by `libblkid`. String values are terminated by `NUL` which is
+/// included in `len`, binary values (e.g. `SBMAGIC`) are not.
+unsafe fn raw_data(data: *const ::libc::c_char, len: usize) -> Vec<u8> {
+    if data.is_null() {
+        return Vec::new();
+    }
+
+    let mut data = std::slice::from_raw_parts(data as *const u8, len).to_vec();
+    if data.last() == Some(&0) {
+        data.pop();
+    }
+    data
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::tag::{PartitionTag, SuperblockTag};
+
+    /// Size of the sparse images, offsets and sector numbers above 2 TiB do not fit 32 bits
+    pub(crate) const LARGE_SIZE: u64 = 3 << 40;
+
+    /// Sparse image file in the temporary directory, removed on drop
+    pub(crate) struct SparseImage {
+        path: PathBuf,
+        file: File,
+    }
+
+    impl SparseImage {
+        pub(crate) fn new(name: &str, size: u64) -> Self {
+            let path =
+                std::env::temp_dir().join(format!("blkid-{}-{}.img", name, std::process::id()));
+            let file = File::options()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .open(&path)
+                .unwrap();
+            file.set_len(size).unwrap();
+            Self { path, file }
+        }
+
+        pub(crate) fn write_at(&self, data: &[u8], offset: u64) {
+            self.file.write_all_at(data, offset).unwrap();
+        }
+
+        pub(crate) fn prober(&self) -> Prober {
+            Prober::open(&self.path, OpenFlags::empty()).unwrap()
+        }
+    }
+
+    impl Drop for SparseImage {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+
+    /// Swap header of a 1 MiB swap area
+    fn swap_header() -> Vec<u8> {
+        let mut data = vec![0; 4096];
+        data[4086..4096].copy_from_slice(b"SWAPSPACE2");
+        data[1024..1028].copy_from_slice(&1u32.to_le_bytes());
+        data[1028..1032].copy_from_slice(&255u32.to_le_bytes());
+        data
+    }
+
+    fn crc32(data: &[u8]) -> u32 {
+        !data.iter().fold(!0u32, |crc, byte| {
+            (0..8).fold(crc ^ u32::from(*byte), |crc, _| {
+                (crc >> 1) ^ (0xedb8_8320 & (crc & 1).wrapping_neg())
+            })
+        })
+    }
+
+    /// Protective MBR, GPT header and entries of a disk of `size` bytes with one Linux
+    /// filesystem partition of `sectors` sectors at the `first_lba` sector
+    fn gpt(size: u64, first_lba: u64, sectors: u64) -> Vec<u8> {
+        let last_lba = size / 512 - 1;
+        let mut data = vec![0; 34 * 512];
+
+        let pmbr = &mut data[446..462];
+        pmbr[4] = 0xee;
+        pmbr[8..12].copy_from_slice(&1u32.to_le_bytes());
+        pmbr[12..16].copy_from_slice(&u32::MAX.to_le_bytes());
+        data[510..512].copy_from_slice(&[0x55, 0xaa]);
+
+        let entry = &mut data[1024..1152];
+        entry[..16].copy_from_slice(&[
+            0xaf, 0x3d, 0xc6, 0x0f, 0x83, 0x84, 0x72, 0x47, 0x8e, 0x79, 0x3d, 0x69, 0xd8, 0x47,
+            0x7d, 0xe4,
+        ]);
+        entry[16..32].fill(0x11);
+        entry[32..40].copy_from_slice(&first_lba.to_le_bytes());
+        entry[40..48].copy_from_slice(&(first_lba + sectors - 1).to_le_bytes());
+        let entries_crc = crc32(&data[1024..34 * 512]);
+
+        let header = &mut data[512..604];
+        header[..8].copy_from_slice(b"EFI PART");
+        header[8..12].copy_from_slice(&0x0001_0000u32.to_le_bytes());
+        header[12..16].copy_from_slice(&92u32.to_le_bytes());
+        header[24..32].copy_from_slice(&1u64.to_le_bytes());
+        header[32..40].copy_from_slice(&last_lba.to_le_bytes());
+        header[40..48].copy_from_slice(&34u64.to_le_bytes());
+        header[48..56].copy_from_slice(&(last_lba - 33).to_le_bytes());
+        header[56..72].fill(0x22);
+        header[72..80].copy_from_slice(&2u64.to_le_bytes());
+        header[80..84].copy_from_slice(&128u32.to_le_bytes());
+        header[84..88].copy_from_slice(&128u32.to_le_bytes());
+        header[88..92].copy_from_slice(&entries_crc.to_le_bytes());
+        let header_crc = crc32(header);
+        header[16..20].copy_from_slice(&header_crc.to_le_bytes());
+        data
+    }
+
+    /// 1 MiB image with a swap signature and a DOS partition table with one partition
+    fn swap_with_mbr() -> Vec<u8> {
+        let mut data = vec![0; 1 << 20];
+        data[4086..4096].copy_from_slice(b"SWAPSPACE2");
+        data[1024..1028].copy_from_slice(&1u32.to_le_bytes());
+        data[1028..1032].copy_from_slice(&255u32.to_le_bytes());
+
+        let entry = &mut data[446..462];
+        entry[4] = 0x83;
+        entry[8..12].copy_from_slice(&2048u32.to_le_bytes());
+        entry[12..16].copy_from_slice(&8u32.to_le_bytes());
+        data[510..512].copy_from_slice(&[0x55, 0xaa]);
+        data
+    }
+
+    #[test]
+    fn probe_all_signatures_collects_every_signature() {
+        let prober = Prober::from_bytes(swap_with_mbr()).unwrap();
+        prober.enable_partitions(true).unwrap();
+
+        let results = prober.probe_all_signatures().unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].get(SuperblockTag::Type), Some("swap"));
+        assert_eq!(results[1].get(PartitionTag::Pttype), Some("dos"));
+
+        // The loop starts from the begin again, the previous Done state is not kept
+        assert_eq!(prober.probe_all_signatures().unwrap(), results);
+        assert_eq!(prober.do_probe().unwrap(), ProbeState::Done);
+    }
+
+    #[test]
+    fn probe_all_signatures_empty_device() {
+        let prober = Prober::from_bytes(vec![0; 1 << 20]).unwrap();
+        assert!(prober.probe_all_signatures().unwrap().is_empty());
+    }
+
+    #[test]
+    fn large_image_size() {
+        let image = SparseImage::new("size", LARGE_SIZE);
+        assert_eq!(image.prober().get_size().unwrap() as u64, LARGE_SIZE);
+    }
+
+    #[test]
+    fn probe_region_above_4_gib() {
+        let offset = 5 << 30;
+        let image = SparseImage::new("region", LARGE_SIZE);
+        image.write_at(&swap_header(), offset);
+
+        let mut prober = image.prober();
+        let result = prober.probe_region(offset as i64, 1 << 20).unwrap();
+        assert_eq!(result.get(SuperblockTag::Type), Some("swap"));
+        assert_eq!(prober.get_offset().unwrap() as u64, offset);
+        assert_eq!(prober.get_size().unwrap(), 1 << 20);
+    }
+
+    #[test]
+    fn partition_beyond_2_tib() {
+        // The start sector itself does not fit 32 bits
+        let start = (5 << 40) / 1024;
+        let image = SparseImage::new("gpt", LARGE_SIZE);
+        image.write_at(&gpt(LARGE_SIZE, start, 2048), 0);
+        image.write_at(&swap_header(), start * 512);
+
+        let mut prober = image.prober();
+        prober.enable_partitions(true).unwrap();
+        let snapshot = prober.partitions_snapshot().unwrap();
+        assert_eq!(snapshot.partitions().len(), 1);
+        assert_eq!(snapshot.partitions()[0].start() as u64, start);
+        assert_eq!(snapshot.partitions()[0].size(), 2048);
+
+        prober.enable_partitions(false).unwrap();
+        let result = prober.probe_region(start as i64 * 512, 2048 * 512).unwrap();
+        assert_eq!(result.get(SuperblockTag::Type), Some("swap"));
     }
 }
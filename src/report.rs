@@ -0,0 +1,222 @@
+//! Combined report of the `NAME=value` and binary probing interfaces
+use crate::{
+    part_table::PartTableInfo, partition::Partition, probe_result::ProbeResult, prober::ProbeState,
+    tag::PartitionTag, topology::Topology, BlkIdResult,
+};
+use std::cmp::Ordering;
+
+/// Result of [`crate::prober::Prober::full_report`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FullReport {
+    pub(crate) state: ProbeState,
+    pub(crate) values: ProbeResult,
+    pub(crate) table: Option<PartTableInfo>,
+    pub(crate) partitions: Vec<PartitionInfo>,
+    pub(crate) topology: Option<TopologyInfo>,
+}
+
+impl FullReport {
+    /// State returned by the safe probing of the enabled chains
+    pub fn state(&self) -> &ProbeState {
+        &self.state
+    }
+
+    /// `NAME=value` results, empty unless [`Self::state`] is [`ProbeState::Success`]
+    pub fn values(&self) -> &ProbeResult {
+        &self.values
+    }
+
+    /// Partition table of the device, `None` if there is no partition table
+    pub fn table(&self) -> Option<&PartTableInfo> {
+        self.table.as_ref()
+    }
+
+    /// Partitions of the partition table on the device
+    pub fn partitions(&self) -> &[PartitionInfo] {
+        &self.partitions
+    }
+
+    /// The partition the device is, decoded from the `PART_ENTRY_*` values. `None` if the device
+    /// is not a partition or the partitions chain runs without
+    /// [`crate::PartitionsFlags::ENTRY_DETAILS`]
+    pub fn entry(&self) -> Option<PartitionInfo> {
+        let get = |tag| self.values.get(tag);
+
+        Some(PartitionInfo {
+            partno: get(PartitionTag::PartEntryNumber)?.parse().ok()?,
+            start: get(PartitionTag::PartEntryOffset)?.parse().ok()?,
+            size: get(PartitionTag::PartEntrySize)?.parse().ok()?,
+            typ: get(PartitionTag::PartEntryType).map(str::to_owned),
+            uuid: get(PartitionTag::PartEntryUuid).map(str::to_owned),
+            name: get(PartitionTag::PartEntryName).map(str::to_owned),
+            flags: get(PartitionTag::PartEntryFlags)
+                .and_then(|flags| u64::from_str_radix(flags.trim_start_matches("0x"), 16).ok())
+                .unwrap_or(0),
+        })
+    }
+
+    /// Topology of the device, `None` if it is not available (e.g. for regular files)
+    pub fn topology(&self) -> Option<&TopologyInfo> {
+        self.topology.as_ref()
+    }
+}
+
+/// Owned information about a partition, in the same units as [`Partition`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PartitionInfo {
+    pub(crate) partno: i32,
+    pub(crate) start: i64,
+    pub(crate) size: i64,
+    pub(crate) typ: Option<String>,
+    pub(crate) uuid: Option<String>,
+    pub(crate) name: Option<String>,
+    pub(crate) flags: u64,
+}
+
+impl PartitionInfo {
+    pub(crate) fn new(partition: &Partition) -> BlkIdResult<Self> {
+        Ok(Self {
+            partno: partition.partno()?,
+            start: partition.start()?,
+            size: partition.size()?,
+            typ: partition
+                .typ_string()
+                .or_else(|| Some(format!("0x{:x}", partition.typ()))),
+            uuid: partition.uuid(),
+            name: partition.name(),
+            flags: partition.flags(),
+        })
+    }
+
+    /// Partition number (e.g. `N` from sda`N`)
+    pub fn partno(&self) -> i32 {
+        self.partno
+    }
+
+    /// Start of the partition in 512-byte sectors
+    pub fn start(&self) -> i64 {
+        self.start
+    }
+
+    /// Size of the partition in 512-byte sectors
+    pub fn size(&self) -> i64 {
+        self.size
+    }
+
+    /// Partition type, `0xNN` for MBR or type UUID for GPT (the same as `PART_ENTRY_TYPE`)
+    pub fn typ(&self) -> Option<&str> {
+        self.typ.as_deref()
+    }
+
+    /// Partition UUID
+    pub fn uuid(&self) -> Option<&str> {
+        self.uuid.as_deref()
+    }
+
+    /// Partition name (GPT and Mac only)
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Partition flags (or attributes for GPT)
+    pub fn flags(&self) -> u64 {
+        self.flags
+    }
+}
+
+/// Orders partitions by their start and then by the partition number, so partitions of out of
+/// order tables can be normalized. The other values only break ties.
+impl Ord for PartitionInfo {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.start, self.partno, self.size, self.flags)
+            .cmp(&(other.start, other.partno, other.size, other.flags))
+            .then_with(|| {
+                (&self.typ, &self.uuid, &self.name).cmp(&(&other.typ, &other.uuid, &other.name))
+            })
+    }
+}
+
+impl PartialOrd for PartitionInfo {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Owned copy of [`Topology`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TopologyInfo {
+    alignment_offset: u64,
+    minimum_io_size: u64,
+    optimal_io_size: u64,
+    logical_sector_size: u64,
+    physical_sector_size: u64,
+}
+
+impl TopologyInfo {
+    pub(crate) fn new(topology: &Topology) -> Self {
+        Self {
+            alignment_offset: topology.alignment_offset(),
+            minimum_io_size: topology.minimum_io_size(),
+            optimal_io_size: topology.optimal_io_size(),
+            logical_sector_size: topology.logical_sector_size(),
+            physical_sector_size: topology.physical_sector_size(),
+        }
+    }
+
+    /// Alignment offset in bytes
+    pub fn alignment_offset(&self) -> u64 {
+        self.alignment_offset
+    }
+
+    /// Minimum I/O size in bytes
+    pub fn minimum_io_size(&self) -> u64 {
+        self.minimum_io_size
+    }
+
+    /// Optimal I/O size in bytes
+    pub fn optimal_io_size(&self) -> u64 {
+        self.optimal_io_size
+    }
+
+    /// Logical sector size in bytes
+    pub fn logical_sector_size(&self) -> u64 {
+        self.logical_sector_size
+    }
+
+    /// Physical sector size in bytes
+    pub fn physical_sector_size(&self) -> u64 {
+        self.physical_sector_size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tag::Tag;
+
+    #[test]
+    fn part_entry_offset_beyond_2_tib() {
+        let start = (5u64 << 40) / 1024;
+        let report = FullReport {
+            state: ProbeState::Success,
+            values: ProbeResult::from(vec![
+                Tag::new(PartitionTag::PartEntryNumber, "1"),
+                Tag::new(PartitionTag::PartEntryOffset, start.to_string()),
+                Tag::new(
+                    PartitionTag::PartEntrySize,
+                    (u64::from(u32::MAX) + 1).to_string(),
+                ),
+            ]),
+            table: None,
+            partitions: Vec::new(),
+            topology: None,
+        };
+
+        let entry = report.entry().unwrap();
+        assert_eq!(entry.start() as u64, start);
+        assert_eq!(entry.size() as u64, u64::from(u32::MAX) + 1);
+    }
+}
@@ -0,0 +1,87 @@
+//! Retrying of probing which fails because of transiently busy devices
+use crate::{
+    prober::{ProbeState, Prober},
+    BlkIdResult,
+};
+use std::{path::Path, thread, time::Duration};
+
+/// Retry policy for transient `EBUSY` and `EAGAIN` failures, common right after partition table
+/// rescans or while udev is processing events for the device
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    attempts: u32,
+    delay: Duration,
+    backoff: u32,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: 5,
+            delay: Duration::from_millis(100),
+            backoff: 2,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Default policy: 5 attempts, the first delay 100 ms doubled after every attempt
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maximum number of attempts including the first one
+    pub fn attempts(mut self, attempts: u32) -> Self {
+        self.attempts = attempts.max(1);
+        self
+    }
+
+    /// Delay before the second attempt
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Multiplier of the delay after every attempt (1 means constant delay)
+    pub fn backoff(mut self, backoff: u32) -> Self {
+        self.backoff = backoff.max(1);
+        self
+    }
+
+    /// Calls `f` until it succeeds, fails with a non-transient error or the attempts are
+    /// exhausted. Returns the last error in the latter case.
+    pub fn run<T, F>(&self, mut f: F) -> BlkIdResult<T>
+    where
+        F: FnMut() -> BlkIdResult<T>,
+    {
+        let mut delay = self.delay;
+        let mut attempt = 1;
+
+        loop {
+            match f() {
+                Err(err) if attempt < self.attempts && err.is_retryable() => {
+                    thread::sleep(delay);
+                    delay = delay.saturating_mul(self.backoff);
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+impl Prober {
+    /// Same as [`Self::new_from_filename`] but retries according to `policy` while the device is
+    /// busy
+    pub fn new_from_filename_with_retry<P: AsRef<Path>>(
+        filename: P,
+        policy: &RetryPolicy,
+    ) -> BlkIdResult<Self> {
+        policy.run(|| Self::new_from_filename(filename.as_ref()))
+    }
+
+    /// Same as [`Self::do_safe_probe`] but retries according to `policy` while the device is busy
+    pub fn do_safe_probe_with_retry(&self, policy: &RetryPolicy) -> BlkIdResult<ProbeState> {
+        policy.run(|| self.do_safe_probe())
+    }
+}
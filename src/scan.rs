@@ -0,0 +1,280 @@
+//! Probing of all block devices in the system
+use crate::{
+    cache::Cache,
+    cancel::CancellationToken,
+    devno::Devno,
+    probe_result::ProbeResult,
+    prober::{ProbeState, Prober},
+    sysfs::{list_block_devices_with, DeviceInfo, ScanOptions},
+    BlkIdError, BlkIdResult, PartitionsFlags, SuperblocksFlags,
+};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::mpsc,
+    thread,
+    time::SystemTime,
+};
+
+/// Block device together with its probing result
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScannedDevice {
+    path: PathBuf,
+    result: ProbeResult,
+}
+
+impl ScannedDevice {
+    /// Creates device from already probed values
+    pub fn new(path: impl Into<PathBuf>, result: ProbeResult) -> Self {
+        Self {
+            path: path.into(),
+            result,
+        }
+    }
+
+    /// Device node (e.g. `/dev/sda1`)
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Probed values, empty if nothing was detected
+    pub fn result(&self) -> &ProbeResult {
+        &self.result
+    }
+}
+
+/// Source of probed devices for the lookup helpers (e.g. [`crate::fs::find_btrfs_members`])
+pub trait DeviceSource {
+    /// Returns all known devices
+    fn scanned_devices(&self) -> BlkIdResult<Vec<ScannedDevice>>;
+}
+
+/// Uses devices stored in the cache. Call [`Cache::probe_all`] before to get up-to-date results.
+impl DeviceSource for Cache {
+    fn scanned_devices(&self) -> BlkIdResult<Vec<ScannedDevice>> {
+        self.devs()?
+            .map(|dev| {
+                let tags = dev.tags()?.collect::<Vec<_>>();
+                Ok(ScannedDevice::new(dev.name(), ProbeResult::from(tags)))
+            })
+            .collect()
+    }
+}
+
+impl DeviceSource for [ScannedDevice] {
+    fn scanned_devices(&self) -> BlkIdResult<Vec<ScannedDevice>> {
+        Ok(self.to_vec())
+    }
+}
+
+impl DeviceSource for Vec<ScannedDevice> {
+    fn scanned_devices(&self) -> BlkIdResult<Vec<ScannedDevice>> {
+        Ok(self.clone())
+    }
+}
+
+/// Probes superblocks and partitions chains of every block device accepted by `options`.
+///
+/// Devices which cannot be opened (e.g. without permissions) or probed (e.g. I/O errors of a
+/// failing disk) and removable drives without medium are skipped, a single device never aborts
+/// the scan. Only a failure to list the devices is returned.
+pub fn scan(options: &ScanOptions) -> BlkIdResult<Vec<ScannedDevice>> {
+    Ok(list_block_devices_with(options)?
+        .iter()
+        .filter_map(|info| scan_device(info, options))
+        .collect())
+}
+
+/// Probes the device for [`scan`], `None` if it cannot be opened or probed
+fn scan_device(info: &DeviceInfo, options: &ScanOptions) -> Option<ScannedDevice> {
+    Prober::open(info.path(), options.get_open_flags())
+        .and_then(|prober| probe_device(&prober, info.path()))
+        .ok()
+}
+
+/// Same as [`scan`] but stops when `token` is cancelled and returns [`BlkIdError::Cancelled`].
+///
+/// Every device is probed in a separate thread, so a device which hangs on reads does not block
+/// the caller beyond the token deadline. The thread of such device is abandoned. Devices are
+/// skipped the same way as in [`scan`], including a device whose probing thread panicked.
+pub fn scan_cancellable(
+    options: &ScanOptions,
+    token: &CancellationToken,
+) -> BlkIdResult<Vec<ScannedDevice>> {
+    let mut devices = Vec::new();
+
+    for info in list_block_devices_with(options)? {
+        token.check()?;
+
+        let (sender, receiver) = mpsc::channel();
+        let options = options.clone();
+        thread::spawn(move || {
+            // The receiver is gone if the scan was cancelled
+            let _ = sender.send(Ok(scan_device(&info, &options)));
+        });
+
+        match token.wait(&receiver) {
+            Ok(device) => devices.extend(device),
+            Err(BlkIdError::Cancelled) => return Err(BlkIdError::Cancelled),
+            // The probing thread panicked, the device is skipped as any other failed device
+            Err(_) => continue,
+        }
+    }
+
+    Ok(devices)
+}
+
+/// Same as [`scan`] but yields the devices as a [`futures::Stream`] as soon as they are probed,
+/// for async consumers:
+///
+/// ```no_run
+/// # use blkid::{scan_stream, ScanOptions};
+/// # use futures::StreamExt;
+/// # async fn run() -> blkid::BlkIdResult<()> {
+/// let mut devices = scan_stream(&ScanOptions::default());
+/// while let Some(device) = devices.next().await {
+///     let device = device?;
+///     println!("{}: {:?}", device.path().display(), device.result().fs_type());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// The probing runs in a separate thread, so the stream does not block the executor. Devices are
+/// skipped the same way as in [`scan`], the stream yields an error and ends only if the devices
+/// cannot be listed. Dropping the stream stops the probing after the current device.
+#[cfg(feature = "futures")]
+pub fn scan_stream(
+    options: &ScanOptions,
+) -> impl futures::Stream<Item = BlkIdResult<ScannedDevice>> + Send + Unpin {
+    let (sender, receiver) = futures::channel::mpsc::unbounded();
+    let options = options.clone();
+
+    thread::spawn(move || {
+        let infos = match list_block_devices_with(&options) {
+            Ok(infos) => infos,
+            Err(err) => {
+                let _ = sender.unbounded_send(Err(err));
+                return;
+            }
+        };
+
+        for device in infos.iter().filter_map(|info| scan_device(info, &options)) {
+            // The receiver is gone if the stream was dropped
+            if sender.unbounded_send(Ok(device)).is_err() {
+                return;
+            }
+        }
+    });
+
+    receiver
+}
+
+/// Opt-in memoization of [`scan`] results for repeated scans of many devices.
+///
+/// A device is probed again only if its identity changed since the previous scan: the device
+/// number, size, disk sequence number ([`DeviceInfo::diskseq`]) or modification time of the
+/// device node. Writes which bypass the device node (e.g. writes to a partition through the whole
+/// disk or through a stacked device) are not detected, use [`Self::invalidate`] after them.
+#[derive(Clone, Debug, Default)]
+pub struct MemoizedScan {
+    entries: HashMap<Devno, (DeviceIdentity, ScannedDevice)>,
+}
+
+/// Values which change when the content of the device may have changed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct DeviceIdentity {
+    size: u64,
+    diskseq: Option<u64>,
+    mtime: Option<SystemTime>,
+}
+
+impl DeviceIdentity {
+    fn new(info: &DeviceInfo) -> Self {
+        Self {
+            size: info.size(),
+            diskseq: info.diskseq(),
+            mtime: fs::metadata(info.path())
+                .and_then(|metadata| metadata.modified())
+                .ok(),
+        }
+    }
+}
+
+impl MemoizedScan {
+    /// Creates an empty memo, the first scan probes all devices
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Same as [`scan`] but returns memoized results of unchanged devices. Devices which are gone
+    /// are forgotten.
+    pub fn scan(&mut self, options: &ScanOptions) -> BlkIdResult<Vec<ScannedDevice>> {
+        let mut entries = HashMap::new();
+        let mut devices = Vec::new();
+
+        for info in list_block_devices_with(options)? {
+            let identity = DeviceIdentity::new(&info);
+            let memoized = self
+                .entries
+                .remove(&info.devno())
+                .filter(|(memoized, device)| *memoized == identity && device.path() == info.path());
+            let device = match memoized {
+                Some((_, device)) => device,
+                None => match scan_device(&info, options) {
+                    Some(device) => device,
+                    None => continue,
+                },
+            };
+
+            entries.insert(info.devno(), (identity, device.clone()));
+            devices.push(device);
+        }
+
+        self.entries = entries;
+        Ok(devices)
+    }
+
+    /// Forces probing of the device in the next scan
+    pub fn invalidate(&mut self, devno: Devno) {
+        self.entries.remove(&devno);
+    }
+
+    /// Forces probing of all devices in the next scan
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Number of memoized devices
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if no device is memoized
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Probes superblocks and partitions chains of the device assigned to `prober`
+pub(crate) fn probe_device(prober: &Prober, path: &Path) -> BlkIdResult<ScannedDevice> {
+    configure(prober)?;
+    let result = safe_probe_result(prober).map_err(|err| err.with_path(path))?;
+    Ok(ScannedDevice::new(path, result))
+}
+
+/// Enables superblocks (including `USAGE`) and partitions (including entry details) chains
+pub(crate) fn configure(prober: &Prober) -> BlkIdResult<()> {
+    prober.set_superblocks_flags(SuperblocksFlags::DEFAULT | SuperblocksFlags::USAGE)?;
+    prober.enable_partitions(true)?;
+    prober.set_partitions_flags(PartitionsFlags::ENTRY_DETAILS)
+}
+
+/// Runs safeprobe, the result is empty if nothing was detected or the result is ambivalent
+pub(crate) fn safe_probe_result(prober: &Prober) -> BlkIdResult<ProbeResult> {
+    match prober.do_safe_probe()? {
+        ProbeState::Success => prober.result(),
+        _ => Ok(ProbeResult::default()),
+    }
+}
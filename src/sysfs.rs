@@ -0,0 +1,538 @@
+//! Enumeration of block devices based on `sysfs`, independent of the `libblkid` cache
+use crate::{devno::Devno, system_roots, BlkIdResult, OpenFlags};
+use bitflags::bitflags;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Size of the sector used by `sysfs` `size` attribute
+const SYSFS_SECTOR_SIZE: u64 = 512;
+
+/// Statically assigned major numbers (see `Documentation/admin-guide/devices.txt`)
+const RAM_MAJOR: u32 = 1;
+const FLOPPY_MAJOR: u32 = 2;
+const LOOP_MAJOR: u32 = 7;
+const MD_MAJOR: u32 = 9;
+
+bitflags! {
+    /// Classes of virtual or legacy block devices which are usually not interesting for inventory
+    #[derive(Default)]
+    pub struct DeviceClass: u32 {
+        /// Loop devices (`/dev/loopN`)
+        const LOOP           = 1 << 0;
+        /// RAM disks (`/dev/ramN`)
+        const RAM            = 1 << 1;
+        /// Compressed RAM disks (`/dev/zramN`)
+        const ZRAM           = 1 << 2;
+        /// Device-mapper devices (`/dev/dm-N`)
+        const DEVICE_MAPPER  = 1 << 3;
+        /// Software RAID devices (`/dev/mdN`)
+        const MD             = 1 << 4;
+        /// Floppy drives (`/dev/fdN`)
+        const FLOPPY         = 1 << 5;
+        /// All classes above
+        const ALL            = Self::LOOP.bits
+            | Self::RAM.bits
+            | Self::ZRAM.bits
+            | Self::DEVICE_MAPPER.bits
+            | Self::MD.bits
+            | Self::FLOPPY.bits;
+    }
+}
+
+/// Options of block device enumeration
+#[derive(Clone, Debug, Default)]
+pub struct ScanOptions {
+    skip: DeviceClass,
+    open_flags: OpenFlags,
+    whole_disks_only: bool,
+}
+
+impl ScanOptions {
+    /// Creates options which accept all devices
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Skips devices of the specified classes. Partitions are skipped together with their disk
+    pub fn skip(mut self, classes: DeviceClass) -> Self {
+        self.skip |= classes;
+        self
+    }
+
+    /// Accepts whole disks only, partitions are skipped. Useful to find raw disk candidates for
+    /// new pools.
+    pub fn whole_disks_only(mut self, whole_disks_only: bool) -> Self {
+        self.whole_disks_only = whole_disks_only;
+        self
+    }
+
+    /// Flags used to open devices by [`crate::scan`], e.g. [`OpenFlags::DIRECT`]
+    pub fn open_flags(mut self, flags: OpenFlags) -> Self {
+        self.open_flags = flags;
+        self
+    }
+
+    /// Returns flags used to open devices
+    pub fn get_open_flags(&self) -> OpenFlags {
+        self.open_flags
+    }
+
+    /// Returns `true` if the device passes the filter
+    pub fn accepts(&self, device: &DeviceInfo) -> bool {
+        if self.whole_disks_only && device.is_partition() {
+            return false;
+        }
+        !self.skip.intersects(device.class())
+    }
+}
+
+/// Basic information about a block device (whole disk or partition) as reported by `sysfs`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeviceInfo {
+    name: String,
+    path: PathBuf,
+    sys_path: PathBuf,
+    devno: Devno,
+    size: u64,
+    removable: bool,
+    rotational: Option<bool>,
+    diskseq: Option<u64>,
+    is_virtual: bool,
+    class: DeviceClass,
+    parent: Option<String>,
+}
+
+impl DeviceInfo {
+    /// Reads information about block device by its kernel name (e.g. `sda` or `nvme0n1p1`)
+    pub fn from_name(name: &str) -> BlkIdResult<Self> {
+        let sys_root = system_roots().get_sys().to_path_buf();
+        let sys_path = fs::canonicalize(sys_root.join("class/block").join(name))?;
+
+        // Partitions are represented as subdirectories of the whole disk directory
+        let (disk_sys_path, parent) = if sys_path.join("partition").exists() {
+            let disk = sys_path.parent().unwrap_or(&sys_path).to_path_buf();
+            let parent = disk.file_name().map(|n| n.to_string_lossy().into_owned());
+            (disk, parent)
+        } else {
+            (sys_path.clone(), None)
+        };
+
+        let devno = read_attr(&sys_path, "dev")?.parse()?;
+        let size = parse_u64_attr(&sys_path, "size")? * SYSFS_SECTOR_SIZE;
+        let removable = read_attr(&disk_sys_path, "removable")
+            .map(|v| v == "1")
+            .unwrap_or(false);
+        let rotational = read_attr(&disk_sys_path, "queue/rotational")
+            .ok()
+            .map(|v| v == "1");
+        let diskseq = read_attr(&disk_sys_path, "diskseq")
+            .ok()
+            .and_then(|v| v.parse().ok());
+        let virtual_root = sys_root.join("devices/virtual");
+        let is_virtual =
+            sys_path.starts_with(fs::canonicalize(&virtual_root).unwrap_or(virtual_root));
+        let disk_name = parent.as_deref().unwrap_or(name);
+        let class = device_class(disk_name, &disk_sys_path, devno);
+
+        Ok(Self {
+            name: name.to_owned(),
+            path: dev_path(name),
+            sys_path,
+            devno,
+            size,
+            removable,
+            rotational,
+            diskseq,
+            is_virtual,
+            class,
+            parent,
+        })
+    }
+
+    /// Kernel name of the device (e.g. `sda1`)
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Path to the device node (e.g. `/dev/sda1`)
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Canonical path to the device directory in `sysfs`
+    pub fn sys_path(&self) -> &Path {
+        &self.sys_path
+    }
+
+    /// Device number
+    pub fn devno(&self) -> Devno {
+        self.devno
+    }
+
+    /// Size of the device in bytes
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Returns `true` if the device (or the whole disk of the partition) is removable
+    pub fn is_removable(&self) -> bool {
+        self.removable
+    }
+
+    /// Returns `true` for spinning disks and `false` for SSDs and NVMe drives, according to
+    /// `queue/rotational` of the whole disk. `None` if the kernel does not report it. Note that
+    /// virtual devices often report the default `true`.
+    ///
+    /// See [`crate::prober::Prober::topology`] for the I/O size hints of the device.
+    pub fn is_rotational(&self) -> Option<bool> {
+        self.rotational
+    }
+
+    /// Disk sequence number of the whole disk, which changes whenever new media is attached (e.g.
+    /// a loop device is reused for another file). `None` if the kernel does not provide it
+    /// (before Linux 5.15)
+    pub fn diskseq(&self) -> Option<u64> {
+        self.diskseq
+    }
+
+    /// Returns `true` if the device (or the whole disk of the partition) is an NVMe namespace
+    pub fn is_nvme(&self) -> bool {
+        self.sys_path
+            .components()
+            .any(|component| component.as_os_str() == "nvme")
+    }
+
+    /// Returns `true` if the device is not backed by hardware (e.g. loop, ram, dm, md)
+    pub fn is_virtual(&self) -> bool {
+        self.is_virtual
+    }
+
+    /// Class of the device (or the whole disk of the partition). Empty for regular disks
+    pub fn class(&self) -> DeviceClass {
+        self.class
+    }
+
+    /// Returns `true` if the device is a partition
+    pub fn is_partition(&self) -> bool {
+        self.parent.is_some()
+    }
+
+    /// Kernel name of the whole disk for partitions, `None` for whole disks
+    pub fn parent(&self) -> Option<&str> {
+        self.parent.as_deref()
+    }
+
+    /// Name of the device-mapper device (e.g. `vg-root`), `None` for other devices
+    pub fn dm_name(&self) -> Option<String> {
+        read_attr(&self.sys_path, "dm/name").ok()
+    }
+
+    /// Devices stacked directly on top of this device (e.g. `md0` for RAID member `sda1`)
+    pub fn holders(&self) -> BlkIdResult<Vec<DeviceInfo>> {
+        self.related("holders")
+    }
+
+    /// Devices this device is stacked directly on top of (e.g. `sda1` and `sdb1` for `md0`)
+    pub fn slaves(&self) -> BlkIdResult<Vec<DeviceInfo>> {
+        self.related("slaves")
+    }
+
+    /// Recursively walks `holders/` links and returns tree of devices built on top of this one
+    /// (e.g. `sda1` → `md0` → `dm-0` (dm-crypt) → `dm-1` (LVM))
+    pub fn holders_tree(&self) -> BlkIdResult<DeviceStack> {
+        DeviceStack::build(self.clone(), &DeviceInfo::holders)
+    }
+
+    /// Recursively walks `slaves/` links and returns tree of devices this one is built from
+    /// (e.g. `dm-1` (LVM) → `dm-0` (dm-crypt) → `md0` → `sda1`)
+    pub fn slaves_tree(&self) -> BlkIdResult<DeviceStack> {
+        DeviceStack::build(self.clone(), &DeviceInfo::slaves)
+    }
+
+    /// Partitions of the whole disk sorted by partition number, empty for partitions and disks
+    /// without partitions
+    pub fn partitions(&self) -> BlkIdResult<Vec<DeviceInfo>> {
+        if self.is_partition() {
+            return Ok(Vec::new());
+        }
+
+        let names = sorted_dir_names(&self.sys_path)?
+            .into_iter()
+            .filter(|name| self.sys_path.join(name).join("partition").exists())
+            .collect::<Vec<_>>();
+
+        // Sorting by name would put `sda10` before `sda2`
+        let mut partitions = devices_from_names(&names)?;
+        partitions
+            .sort_by_cached_key(|partition| parse_u64_attr(&partition.sys_path, "partition").ok());
+        Ok(partitions)
+    }
+
+    fn related(&self, dir: &str) -> BlkIdResult<Vec<DeviceInfo>> {
+        let dir = self.sys_path.join(dir);
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        devices_from_names(&sorted_dir_names(&dir)?)
+    }
+}
+
+#[cfg(feature = "udev")]
+impl DeviceInfo {
+    /// Reads information about the block device of a `udev` device. Fails if the device is not
+    /// a block device or if its device number does not match `sysfs` (e.g. the device was
+    /// removed and its name reused meanwhile).
+    pub fn from_udev(device: &udev::Device) -> BlkIdResult<Self> {
+        let devno = udev_devno(device).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} is not a block device", device.syspath().display()),
+            )
+        })?;
+
+        let info = Self::from_name(&device.sysname().to_string_lossy())?;
+        if info.devno != devno {
+            return Err(crate::BlkIdError::InvalidDevno(devno.to_string()));
+        }
+        Ok(info)
+    }
+
+    /// Finds the `udev` device with the same device number among the devices of `enumerator`,
+    /// `None` if there is no such device. The enumerator is mutable because `libudev` scans on
+    /// every call, add matches (e.g. `match_subsystem("block")`) to speed the scan up.
+    pub fn to_udev_device(
+        &self,
+        enumerator: &mut udev::Enumerator,
+    ) -> BlkIdResult<Option<udev::Device>> {
+        Ok(enumerator
+            .scan_devices()?
+            .find(|device| udev_devno(device) == Some(self.devno)))
+    }
+}
+
+/// Node of the storage stacking graph
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeviceStack {
+    device: DeviceInfo,
+    children: Vec<DeviceStack>,
+}
+
+impl DeviceStack {
+    fn build(
+        device: DeviceInfo,
+        next: &dyn Fn(&DeviceInfo) -> BlkIdResult<Vec<DeviceInfo>>,
+    ) -> BlkIdResult<Self> {
+        let children = next(&device)?
+            .into_iter()
+            .map(|child| Self::build(child, next))
+            .collect::<BlkIdResult<_>>()?;
+        Ok(Self { device, children })
+    }
+
+    /// Device of this node
+    pub fn device(&self) -> &DeviceInfo {
+        &self.device
+    }
+
+    /// Next level of the stack (holders or slaves depending on the walk direction)
+    pub fn children(&self) -> &[DeviceStack] {
+        &self.children
+    }
+
+    /// Returns `true` if nothing is stacked on (or below) the device
+    pub fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    /// All devices of the tree in depth-first order, starting with the device of this node
+    pub fn devices(&self) -> Vec<&DeviceInfo> {
+        let mut devices = vec![&self.device];
+        for child in &self.children {
+            devices.extend(child.devices());
+        }
+        devices
+    }
+}
+
+/// Returns all block devices found in `/sys/block` including their partitions.
+///
+/// Unlike [`crate::cache::Cache`] this does not read nor write `blkid.tab` and does not probe the
+/// devices, so the returned paths can be used to drive [`crate::prober::Prober`] directly.
+pub fn list_block_devices() -> BlkIdResult<Vec<DeviceInfo>> {
+    list_block_devices_with(&ScanOptions::default())
+}
+
+/// Same as [`list_block_devices`] but returns only devices accepted by `options`.
+///
+/// Devices removed while they are listed (e.g. unplugged disks or detached loop devices) are
+/// skipped.
+pub fn list_block_devices_with(options: &ScanOptions) -> BlkIdResult<Vec<DeviceInfo>> {
+    let mut devices = Vec::new();
+    let disks = devices_from_names(&sorted_dir_names(&system_roots().get_sys().join("block"))?)?;
+
+    for disk_info in disks {
+        if !options.accepts(&disk_info) {
+            continue;
+        }
+        if options.whole_disks_only {
+            devices.push(disk_info);
+            continue;
+        }
+
+        let partitions = match disk_info.partitions() {
+            Ok(partitions) => partitions,
+            Err(err) if err.is_not_found() => continue,
+            Err(err) => return Err(err),
+        };
+        devices.push(disk_info);
+        devices.extend(partitions);
+    }
+
+    Ok(devices)
+}
+
+fn device_class(disk_name: &str, disk_sys_path: &Path, devno: Devno) -> DeviceClass {
+    if devno.major() == LOOP_MAJOR || disk_name.starts_with("loop") {
+        DeviceClass::LOOP
+    } else if devno.major() == RAM_MAJOR || disk_name.starts_with("ram") {
+        DeviceClass::RAM
+    } else if disk_name.starts_with("zram") {
+        DeviceClass::ZRAM
+    } else if disk_sys_path.join("dm").exists() {
+        DeviceClass::DEVICE_MAPPER
+    } else if devno.major() == MD_MAJOR || disk_sys_path.join("md").exists() {
+        DeviceClass::MD
+    } else if devno.major() == FLOPPY_MAJOR || disk_name.starts_with("fd") {
+        DeviceClass::FLOPPY
+    } else {
+        DeviceClass::empty()
+    }
+}
+
+/// Device number of a `udev` device of the `block` subsystem
+#[cfg(feature = "udev")]
+fn udev_devno(device: &udev::Device) -> Option<Devno> {
+    if device.subsystem()? != "block" {
+        return None;
+    }
+    device.devnum().map(Devno::from_dev_t)
+}
+
+/// Converts kernel name to the path of device node. The `!` in kernel names stands for `/` (e.g.
+/// `cciss!c0d0` is `/dev/cciss/c0d0`)
+fn dev_path(name: &str) -> PathBuf {
+    system_roots().get_dev().join(name.replace('!', "/"))
+}
+
+/// Reads the devices of kernel `names`, skipping devices which disappeared since the names were
+/// listed
+fn devices_from_names(names: &[String]) -> BlkIdResult<Vec<DeviceInfo>> {
+    let mut devices = Vec::new();
+    for name in names {
+        match DeviceInfo::from_name(name) {
+            Ok(device) => devices.push(device),
+            Err(err) if err.is_not_found() => continue,
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(devices)
+}
+
+fn sorted_dir_names(dir: &Path) -> BlkIdResult<Vec<String>> {
+    let mut names = fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.file_name().to_string_lossy().into_owned()))
+        .collect::<io::Result<Vec<_>>>()?;
+    names.sort();
+    Ok(names)
+}
+
+fn read_attr(sys_path: &Path, attr: &str) -> BlkIdResult<String> {
+    Ok(fs::read_to_string(sys_path.join(attr))?.trim().to_owned())
+}
+
+fn parse_u64_attr(sys_path: &Path, attr: &str) -> BlkIdResult<u64> {
+    let value = read_attr(sys_path, attr)?;
+    value.parse().map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "invalid value of {}: {:?}",
+                sys_path.join(attr).display(),
+                value
+            ),
+        )
+        .into()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{set_system_roots, SystemRoots};
+    use std::{os::unix::fs::symlink, sync::OnceLock};
+
+    /// Fake `sysfs` with disk `sda`, its partitions and devices removed while they were listed,
+    /// shared by the tests because the system roots are process-wide
+    fn fake_sysfs() {
+        static ROOT: OnceLock<PathBuf> = OnceLock::new();
+        let root = ROOT.get_or_init(|| {
+            let root = std::env::temp_dir().join(format!("blkid-sysfs-{}", std::process::id()));
+            let _ = fs::remove_dir_all(&root);
+            let sys = root.join("sys");
+            let disk = sys.join("devices/sda");
+            fs::create_dir_all(sys.join("block")).unwrap();
+            fs::create_dir_all(sys.join("class/block")).unwrap();
+
+            let attrs = |dir: &Path, devno: &str, partition: Option<&str>| {
+                fs::create_dir_all(dir).unwrap();
+                fs::write(dir.join("dev"), devno).unwrap();
+                fs::write(dir.join("size"), "2048\n").unwrap();
+                if let Some(partition) = partition {
+                    fs::write(dir.join("partition"), partition).unwrap();
+                }
+            };
+            attrs(&disk, "8:0\n", None);
+            symlink("../devices/sda", sys.join("block/sda")).unwrap();
+            symlink("../../devices/sda", sys.join("class/block/sda")).unwrap();
+            for partno in [1, 2, 10] {
+                let name = format!("sda{}", partno);
+                attrs(
+                    &disk.join(&name),
+                    &format!("8:{}\n", partno),
+                    Some(&format!("{}\n", partno)),
+                );
+                symlink(
+                    format!("../../devices/sda/{}", name),
+                    sys.join("class/block").join(&name),
+                )
+                .unwrap();
+            }
+
+            // Disk `sdb` and partition `sda3` were removed after their names were listed
+            symlink("../devices/sdb", sys.join("block/sdb")).unwrap();
+            symlink("../../devices/sdb", sys.join("class/block/sdb")).unwrap();
+            attrs(&disk.join("sda3"), "8:3\n", Some("3\n"));
+            root
+        });
+        set_system_roots(SystemRoots::under(root));
+    }
+
+    #[test]
+    fn list_skips_removed_devices() {
+        fake_sysfs();
+        let devices = list_block_devices().unwrap();
+        let names = devices.iter().map(DeviceInfo::name).collect::<Vec<_>>();
+        assert_eq!(names, ["sda", "sda1", "sda2", "sda10"]);
+    }
+
+    #[test]
+    fn partitions_sorted_by_number() {
+        fake_sysfs();
+        let partitions = DeviceInfo::from_name("sda").unwrap().partitions().unwrap();
+        let names = partitions.iter().map(DeviceInfo::name).collect::<Vec<_>>();
+        assert_eq!(names, ["sda1", "sda2", "sda10"]);
+    }
+}
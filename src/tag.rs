@@ -1,17 +1,22 @@
-use crate::dev::Dev;
+use crate::{
+    dev::Dev,
+    error::{c_result, BlkIdError, BlkIdResult},
+};
 use blkid_sys::*;
-use std::{ffi::CStr, ptr, str::FromStr};
-use strum_macros::{Display, EnumString};
+use std::{
+    ffi::{CStr, CString},
+    ptr,
+    str::FromStr,
+};
 
 pub struct Tags {
     pub(crate) iter: blkid_tag_iterate,
 }
 
 impl Tags {
-    pub fn new(dev: &Dev) -> Tags {
-        let iter = unsafe { blkid_tag_iterate_begin(dev.0) };
-        assert_ne!(iter, ptr::null_mut());
-        Tags { iter }
+    pub fn new(dev: &Dev) -> BlkIdResult<Tags> {
+        let iter = unsafe { c_result(blkid_tag_iterate_begin(dev.0)) }?;
+        Ok(Tags { iter })
     }
 }
 
@@ -42,19 +47,30 @@ impl Iterator for Tags {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Tag {
     name: TagType,
     value: String,
 }
 
 impl Tag {
-    pub fn new(name: impl Into<TagType>, value: &str) -> Self {
+    pub fn new(name: impl Into<TagType>, value: impl Into<String>) -> Self {
         Self {
             name: name.into(),
-            value: value.to_owned(),
+            value: value.into(),
         }
     }
 
+    /// Filesystem UUID tag
+    pub fn uuid(value: impl Into<String>) -> Self {
+        Self::new(SuperblockTag::Uuid, value)
+    }
+
+    /// Filesystem label tag
+    pub fn label(value: impl Into<String>) -> Self {
+        Self::new(SuperblockTag::Label, value)
+    }
+
     pub fn typ(&self) -> TagType {
         self.name.clone()
     }
@@ -68,6 +84,39 @@ impl Tag {
     }
 }
 
+/// Prints the tag as `NAME="value"`, the same way as `blkid(8)` does
+impl std::fmt::Display for Tag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}=\"{}\"", self.name, escape_value(&self.value, "\\\""))
+    }
+}
+
+/// Parses the `NAME=value` syntax used by `fstab` and `blkid(8)` (e.g. `LABEL="My Disk"` or
+/// `UUID=1234-ABCD`), the optional quotes around the value are removed
+impl FromStr for Tag {
+    type Err = BlkIdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let token = CString::new(s)?;
+        let mut name = ptr::null_mut();
+        let mut value = ptr::null_mut();
+
+        if unsafe { blkid_parse_tag_string(token.as_ptr(), &mut name, &mut value) } != 0 {
+            return Err(BlkIdError::InvalidTag(s.to_owned()));
+        }
+
+        let (name, value) = unsafe { (take_string(name), take_string(value)) };
+        Ok(Self::new(TagType::from(name?.as_str()), value?))
+    }
+}
+
+/// Converts string allocated by `libblkid` and frees it
+unsafe fn take_string(ptr: *mut libc::c_char) -> BlkIdResult<String> {
+    let string = CStr::from_ptr(ptr).to_str().map(str::to_owned);
+    libc::free(ptr as *mut libc::c_void);
+    Ok(string?)
+}
+
 /// This is unified form of tag types.
 /// Each of inner enum value implement `From` trait, which allows to construct this enum using the
 /// following syntax:
@@ -79,7 +128,7 @@ impl Tag {
 ///
 /// assert_eq!(tag_type, tag_part_type);
 /// ```
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum TagType {
     Superblock(SuperblockTag),
     Partition(PartitionTag),
@@ -113,49 +162,78 @@ impl From<&str> for TagType {
     }
 }
 
-#[derive(Clone, Debug, Display, Eq, PartialEq, EnumString)]
-#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
-pub enum SuperblockTag {
-    /// Filesystem type
-    Type,
-    /// Secondary filesystem type
-    SecType,
-    /// Filesystem label
-    Label,
-    /// Raw label from FS superblock
-    LabelRaw,
-    /// Filesystem UUID (lower case)
-    Uuid,
-    /// Subvolume uuid (e.g. btrfs)
-    UuidSub,
-    /// External log UUID (e.g. xfs)
-    Loguuid,
-    /// Raw UUID from FS superblock
-    UuidRaw,
-    /// External journal UUID
-    ExtJournal,
-    /// Usage string: "raid", "filesystem", ...
-    Usage,
-    /// Filesystem version
-    Version,
-    /// Cluster mount name (?) -- ocfs only
-    Mount,
-    /// Super block magic string
-    Sbmagic,
-    /// Offset of SBMAGIC
-    SbmagicOffset,
-    /// Size of filesystem [not-implemented yet]
-    Fssize,
-    /// ISO9660 system identifier
-    SystemId,
-    /// ISO9660 publisher identifier
-    PublisherId,
-    /// ISO9660 application identifier
-    ApplicationId,
-    /// ISO9660 boot system identifier
-    BootSystemId,
-    /// Block size
-    BlockSize,
+/// Serialized as the value name (e.g. "LABEL")
+#[cfg(feature = "serde")]
+impl serde::Serialize for TagType {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for TagType {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Ok(Self::from(name.as_str()))
+    }
+}
+
+string_enum! {
+    #[derive(Clone, Debug, Eq, PartialEq, Hash)]
+    pub enum SuperblockTag {
+        /// Filesystem type
+        Type = "TYPE",
+        /// Secondary filesystem type
+        SecType = "SEC_TYPE",
+        /// Filesystem label
+        Label = "LABEL",
+        /// Raw label from FS superblock
+        LabelRaw = "LABEL_RAW",
+        /// Filesystem UUID (lower case)
+        Uuid = "UUID",
+        /// Subvolume uuid (e.g. btrfs)
+        UuidSub = "UUID_SUB",
+        /// External log UUID (e.g. xfs)
+        Loguuid = "LOGUUID",
+        /// Raw UUID from FS superblock
+        UuidRaw = "UUID_RAW",
+        /// External journal UUID
+        ExtJournal = "EXT_JOURNAL",
+        /// Usage string: "raid", "filesystem", ...
+        Usage = "USAGE",
+        /// Filesystem version
+        Version = "VERSION",
+        /// Cluster mount name (?) -- ocfs only
+        Mount = "MOUNT",
+        /// Super block magic string
+        Sbmagic = "SBMAGIC",
+        /// Offset of SBMAGIC
+        SbmagicOffset = "SBMAGIC_OFFSET",
+        /// Size of filesystem [not-implemented yet]
+        Fssize = "FSSIZE",
+        /// Last block of the filesystem
+        Fslastblock = "FSLASTBLOCK",
+        /// Filesystem block size
+        Fsblocksize = "FSBLOCKSIZE",
+        /// ISO9660 system identifier
+        SystemId = "SYSTEM_ID",
+        /// ISO9660 publisher identifier
+        PublisherId = "PUBLISHER_ID",
+        /// ISO9660 application identifier
+        ApplicationId = "APPLICATION_ID",
+        /// ISO9660 boot system identifier
+        BootSystemId = "BOOT_SYSTEM_ID",
+        /// ISO9660 and UDF volume identifier
+        VolumeId = "VOLUME_ID",
+        /// ISO9660 and UDF volume set identifier
+        VolumeSetId = "VOLUME_SET_ID",
+        /// ISO9660 data preparer identifier
+        DataPreparerId = "DATA_PREPARER_ID",
+        /// Label from the FAT boot sector (may differ from the root directory label)
+        LabelFatboot = "LABEL_FATBOOT",
+        /// Block size
+        BlockSize = "BLOCK_SIZE",
+    }
 }
 
 impl From<SuperblockTag> for TagType {
@@ -164,31 +242,32 @@ impl From<SuperblockTag> for TagType {
     }
 }
 
-#[derive(Clone, Debug, Display, Eq, PartialEq, EnumString)]
-#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
-pub enum PartitionTag {
-    /// Partition table type (dos, gpt, etc.)
-    Pttype,
-    /// Partition table id (uuid for gpt, hex for dos)
-    Ptuuid,
-    /// Partition table type
-    PartEntrySchema,
-    /// Partition name (gpt and mac only)
-    PartEntryName,
-    /// Partition UUID (gpt, or pseudo IDs for MBR)
-    PartEntryUuid,
-    /// Partition type, 0xNN (e.g. 0x82) or type UUID (gpt only) or type string (mac)
-    PartEntryType,
-    /// Partition flags (e.g. boot_ind) or attributes (e.g. gpt attributes)
-    PartEntryFlags,
-    /// Partition number
-    PartEntryNumber,
-    /// The begin of the partition
-    PartEntryOffset,
-    /// Size of the partition
-    PartEntrySize,
-    /// Whole-disk maj:min
-    PartEntryDisk,
+string_enum! {
+    #[derive(Clone, Debug, Eq, PartialEq, Hash)]
+    pub enum PartitionTag {
+        /// Partition table type (dos, gpt, etc.)
+        Pttype = "PTTYPE",
+        /// Partition table id (uuid for gpt, hex for dos)
+        Ptuuid = "PTUUID",
+        /// Partition table type
+        PartEntrySchema = "PART_ENTRY_SCHEME",
+        /// Partition name (gpt and mac only)
+        PartEntryName = "PART_ENTRY_NAME",
+        /// Partition UUID (gpt, or pseudo IDs for MBR)
+        PartEntryUuid = "PART_ENTRY_UUID",
+        /// Partition type, 0xNN (e.g. 0x82) or type UUID (gpt only) or type string (mac)
+        PartEntryType = "PART_ENTRY_TYPE",
+        /// Partition flags (e.g. boot_ind) or attributes (e.g. gpt attributes)
+        PartEntryFlags = "PART_ENTRY_FLAGS",
+        /// Partition number
+        PartEntryNumber = "PART_ENTRY_NUMBER",
+        /// The begin of the partition
+        PartEntryOffset = "PART_ENTRY_OFFSET",
+        /// Size of the partition
+        PartEntrySize = "PART_ENTRY_SIZE",
+        /// Whole-disk maj:min
+        PartEntryDisk = "PART_ENTRY_DISK",
+    }
 }
 
 impl From<PartitionTag> for TagType {
@@ -197,23 +276,24 @@ impl From<PartitionTag> for TagType {
     }
 }
 
-#[derive(Clone, Debug, Display, Eq, PartialEq, EnumString)]
-#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
-pub enum TopologyTag {
-    /// The smallest unit the storage device can address. It is typically 512 bytes
-    LogicalSectorSize,
-    /// The smallest unit a physical storage device can write atomically. It is usually the same as
-    /// the logical sector size but may be bigger.
-    PhysicalSectorSize,
-    /// Minimum size which is the device's preferred unit of I/O. For RAID arrays it is often the
-    /// stripe chunk size
-    MinimumIoSize,
-    /// Usually the stripe width for RAID or zero. For RAID arrays it is usually the stripe width
-    /// or the internal track size
-    OptiomalIoSize,
-    /// Indicates how many bytes the beginning of the device is offset from the disk's natural
-    /// alignment
-    AlignmentOffset,
+string_enum! {
+    #[derive(Clone, Debug, Eq, PartialEq, Hash)]
+    pub enum TopologyTag {
+        /// The smallest unit the storage device can address. It is typically 512 bytes
+        LogicalSectorSize = "LOGICAL_SECTOR_SIZE",
+        /// The smallest unit a physical storage device can write atomically. It is usually the same as
+        /// the logical sector size but may be bigger.
+        PhysicalSectorSize = "PHYSICAL_SECTOR_SIZE",
+        /// Minimum size which is the device's preferred unit of I/O. For RAID arrays it is often the
+        /// stripe chunk size
+        MinimumIoSize = "MINIMUM_IO_SIZE",
+        /// Usually the stripe width for RAID or zero. For RAID arrays it is usually the stripe width
+        /// or the internal track size
+        OptiomalIoSize = "OPTIMAL_IO_SIZE",
+        /// Indicates how many bytes the beginning of the device is offset from the disk's natural
+        /// alignment
+        AlignmentOffset = "ALIGNMENT_OFFSET",
+    }
 }
 
 impl From<TopologyTag> for TagType {
@@ -221,3 +301,56 @@ impl From<TopologyTag> for TagType {
         Self::Topoligy(tag)
     }
 }
+
+/// Encodes potentially unsafe characters of `value` (e.g. whitespace, `/` or `\`) as `\xNN`,
+/// the format of the udev `*_ENC` properties
+pub fn encode_string(value: &str) -> BlkIdResult<String> {
+    convert_string(value, value.len() * 4 + 1, |value, buf, len| unsafe {
+        blkid_encode_string(value, buf, len)
+    })
+}
+
+/// Replaces whitespace in `value` with `_` and strips trailing whitespace, the format of the
+/// udev `ID_FS_LABEL` and `ID_FS_UUID` properties
+pub fn safe_string(value: &str) -> BlkIdResult<String> {
+    convert_string(value, value.len() + 1, |value, buf, len| unsafe {
+        blkid_safe_string(value, buf, len)
+    })
+}
+
+fn convert_string<F>(value: &str, len: usize, convert: F) -> BlkIdResult<String>
+where
+    F: FnOnce(*const libc::c_char, *mut libc::c_char, usize) -> i32,
+{
+    let value = CString::new(value)?;
+    let mut buf = vec![0u8; len];
+    let buf_ptr = buf.as_mut_ptr() as *mut libc::c_char;
+    c_result(convert(value.as_ptr(), buf_ptr, len))?;
+    let converted = CStr::from_bytes_until_nul(&buf).unwrap_or_default();
+    Ok(converted.to_str()?.to_owned())
+}
+
+/// Escapes `value` like `blkid(8)`: bytes above 127 are printed as `M-` followed by the byte
+/// minus 128, control characters as `^` notation (e.g. `^I`) and characters from `esc` are
+/// prefixed by `\`
+pub(crate) fn escape_value(value: &str, esc: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for mut byte in value.bytes() {
+        if byte >= 128 {
+            escaped.push_str("M-");
+            byte -= 128;
+        }
+        if byte < 32 || byte == 0x7f {
+            escaped.push('^');
+            byte ^= 0x40;
+        } else if esc.as_bytes().contains(&byte) {
+            escaped.push('\\');
+        }
+        escaped.push(char::from(byte));
+    }
+    escaped
+}
+
+/// Names of the values as C strings, see [`crate::tags::cstr`]
+pub use crate::tags::cstr;
@@ -0,0 +1,134 @@
+//! Names of the `NAME=value` pairs reported by `libblkid`, for code which works with the raw
+//! names (e.g. [`crate::prober::Prober::lookup_value`] or udev properties) instead of
+//! [`crate::tag::TagType`]
+//!
+//! ```
+//! use blkid::{tag::SuperblockTag, tags};
+//!
+//! assert_eq!(tags::UUID, SuperblockTag::Uuid.as_str());
+//! ```
+
+value_names! {
+    /// Filesystem type
+    TYPE,
+    /// Secondary filesystem type
+    SEC_TYPE,
+    /// Filesystem label
+    LABEL,
+    /// Raw label from the superblock
+    LABEL_RAW,
+    /// Filesystem UUID (lower case)
+    UUID,
+    /// Subvolume or member UUID (e.g. btrfs, RAID)
+    UUID_SUB,
+    /// External log UUID (e.g. xfs)
+    LOGUUID,
+    /// Raw UUID from the superblock
+    UUID_RAW,
+    /// External journal UUID
+    EXT_JOURNAL,
+    /// Usage: `filesystem`, `raid`, `crypto`, `other`
+    USAGE,
+    /// Filesystem version
+    VERSION,
+    /// Cluster mount name (ocfs only)
+    MOUNT,
+    /// Superblock magic string
+    SBMAGIC,
+    /// Offset of [`SBMAGIC`]
+    SBMAGIC_OFFSET,
+    /// Size of the filesystem
+    FSSIZE,
+    /// Last block of the filesystem
+    FSLASTBLOCK,
+    /// Filesystem block size
+    FSBLOCKSIZE,
+    /// ISO9660 system identifier
+    SYSTEM_ID,
+    /// ISO9660 publisher identifier
+    PUBLISHER_ID,
+    /// ISO9660 application identifier
+    APPLICATION_ID,
+    /// ISO9660 boot system identifier
+    BOOT_SYSTEM_ID,
+    /// ISO9660 and UDF volume identifier
+    VOLUME_ID,
+    /// ISO9660 and UDF volume set identifier
+    VOLUME_SET_ID,
+    /// ISO9660 data preparer identifier
+    DATA_PREPARER_ID,
+    /// Label from the FAT boot sector
+    LABEL_FATBOOT,
+    /// Minimal block size accessible by the filesystem
+    BLOCK_SIZE,
+
+    /// Partition table type (dos, gpt, ...)
+    PTTYPE,
+    /// Partition table ID (GPT disk UUID or DOS disk ID)
+    PTUUID,
+    /// Partition table type of the partition
+    PART_ENTRY_SCHEME,
+    /// Partition name (GPT and Mac only)
+    PART_ENTRY_NAME,
+    /// Partition UUID (GPT, or pseudo IDs for MBR)
+    PART_ENTRY_UUID,
+    /// Partition type, `0xNN` (MBR) or type UUID (GPT) or type string (Mac)
+    PART_ENTRY_TYPE,
+    /// Partition flags (e.g. boot indicator) or GPT attributes
+    PART_ENTRY_FLAGS,
+    /// Partition number
+    PART_ENTRY_NUMBER,
+    /// Start of the partition in 512-byte sectors
+    PART_ENTRY_OFFSET,
+    /// Size of the partition in 512-byte sectors
+    PART_ENTRY_SIZE,
+    /// Whole disk `MAJ:MIN`
+    PART_ENTRY_DISK,
+
+    /// Logical sector size in bytes
+    LOGICAL_SECTOR_SIZE,
+    /// Physical sector size in bytes
+    PHYSICAL_SECTOR_SIZE,
+    /// Minimum I/O size in bytes
+    MINIMUM_IO_SIZE,
+    /// Optimal I/O size in bytes
+    OPTIMAL_IO_SIZE,
+    /// Alignment offset in bytes
+    ALIGNMENT_OFFSET,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tag::{PartitionTag, SuperblockTag, TagType, TopologyTag};
+
+    #[test]
+    fn enum_names_have_constants() {
+        let names = SuperblockTag::ALL
+            .iter()
+            .map(SuperblockTag::as_str)
+            .chain(PartitionTag::ALL.iter().map(PartitionTag::as_str))
+            .chain(TopologyTag::ALL.iter().map(TopologyTag::as_str));
+
+        for name in names {
+            assert!(ALL.contains(&name), "missing constant for {name}");
+        }
+    }
+
+    #[test]
+    fn constants_resolve_to_enums() {
+        for name in ALL {
+            assert!(
+                !matches!(TagType::from(*name), TagType::Unknown(_)),
+                "{name} is not covered by the enums"
+            );
+        }
+    }
+
+    #[test]
+    fn cstr_constants_match() {
+        assert_eq!(cstr::PART_ENTRY_SCHEME.to_str(), Ok(PART_ENTRY_SCHEME));
+        assert_eq!(cstr::OPTIMAL_IO_SIZE.to_str(), Ok(OPTIMAL_IO_SIZE));
+        assert_eq!(cstr::TYPE.to_bytes_with_nul(), b"TYPE\0");
+    }
+}
@@ -1,37 +1,109 @@
+use crate::prober::Prober;
 use blkid_sys::*;
+use std::marker::PhantomData;
+
+/// Traditional sector size
+const LEGACY_SECTOR_SIZE: u64 = 512;
 
 /// Device topology information
-pub struct Topology(pub(crate) blkid_topology);
+///
+/// The topology borrows the [`Prober`] it was returned from mutably, so no other prober call (e.g.
+/// [`Prober::do_probe`], [`Prober::set_device`] or another [`Prober::topology`]) which resets the
+/// topology can run while it is alive. See [`crate::report::TopologyInfo`] for an owned copy.
+pub struct Topology<'a>(pub(crate) blkid_topology, PhantomData<&'a mut Prober>);
+
+/// Filesystem block size which is smaller than the physical sector size of the device. Every write
+/// of such a block forces the device to read-modify-write the whole physical sector.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BlockSizeWarning {
+    block_size: u64,
+    physical_sector_size: u64,
+}
+
+impl BlockSizeWarning {
+    /// Filesystem block size in bytes
+    pub fn block_size(&self) -> u64 {
+        self.block_size
+    }
+
+    /// Physical sector size of the device in bytes
+    pub fn physical_sector_size(&self) -> u64 {
+        self.physical_sector_size
+    }
+}
+
+impl<'a> Topology<'a> {
+    pub(crate) fn new(topology: blkid_topology) -> Self {
+        Self(topology, PhantomData)
+    }
 
-impl Topology {
     /// Alignment offset in bytes or 0.
     pub fn alignment_offset(&self) -> u64 {
-        unsafe { blkid_topology_get_alignment_offset(self.0) }.try_into().unwrap()
+        unsafe { blkid_topology_get_alignment_offset(self.0) as u64 }
     }
 
     /// Minimum io size in bytes or 0.
     pub fn minimum_io_size(&self) -> u64 {
-        unsafe { blkid_topology_get_minimum_io_size(self.0) }.try_into().unwrap()
+        unsafe { blkid_topology_get_minimum_io_size(self.0) as u64 }
     }
 
     /// Optimal io size in bytes or 0.
     pub fn optimal_io_size(&self) -> u64 {
-        unsafe { blkid_topology_get_optimal_io_size(self.0) }.try_into().unwrap()
+        unsafe { blkid_topology_get_optimal_io_size(self.0) as u64 }
     }
 
     /// Logical sector size (BLKSSZGET ioctl) in bytes or 0.
     pub fn logical_sector_size(&self) -> u64 {
-        unsafe { blkid_topology_get_logical_sector_size(self.0) }.try_into().unwrap()
+        unsafe { blkid_topology_get_logical_sector_size(self.0) as u64 }
     }
 
     /// Logical sector size (BLKSSZGET ioctl) in bytes or 0.
     pub fn physical_sector_size(&self) -> u64 {
-        unsafe { blkid_topology_get_physical_sector_size(self.0) }.try_into().unwrap()
+        unsafe { blkid_topology_get_physical_sector_size(self.0) as u64 }
     }
 
-    /// Returns `true` if dax is supported
-    #[cfg(blkid = "2.36")]
-    pub fn dax(&self) -> bool {
-        unsafe { blkid_topology_get_dax(self.0) == 1 }
+    /// Returns `true` for advanced format devices, i.e. devices with physical sectors bigger than
+    /// 512 bytes. It covers both 512e (512-byte logical sectors emulated on top of 4K physical
+    /// sectors) and 4Kn (native 4K logical sectors) devices.
+    pub fn is_advanced_format(&self) -> bool {
+        self.physical_sector_size() > LEGACY_SECTOR_SIZE
+            || self.logical_sector_size() > LEGACY_SECTOR_SIZE
+    }
+
+    /// Number of logical sectors per physical sector (e.g. 8 for 512e devices), `None` if the
+    /// sector sizes are unknown.
+    pub fn logical_physical_ratio(&self) -> Option<u64> {
+        let logical = self.logical_sector_size();
+        let physical = self.physical_sector_size();
+        if logical == 0 || physical == 0 {
+            None
+        } else {
+            Some(physical / logical)
+        }
+    }
+
+    /// Checks filesystem block size (e.g. `BLOCK_SIZE` from the superblocks chain, see
+    /// [`crate::probe_result::ProbeResult::block_size`]) against the physical sector size.
+    /// Returns warning if the block size is smaller.
+    pub fn check_block_size(&self, block_size: u64) -> Option<BlockSizeWarning> {
+        let physical_sector_size = self.physical_sector_size();
+        if block_size > 0 && block_size < physical_sector_size {
+            Some(BlockSizeWarning {
+                block_size,
+                physical_sector_size,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Returns `Some(true)` if dax is supported, `None` if the linked `libblkid` is older than 2.36
+    /// and does not provide the information
+    pub fn dax(&self) -> Option<bool> {
+        #[cfg(blkid = "2.36")]
+        return Some(unsafe { blkid_topology_get_dax(self.0) == 1 });
+
+        #[cfg(not(blkid = "2.36"))]
+        None
     }
 }
@@ -0,0 +1,44 @@
+//! Miscellaneous `libblkid` utilities to correlate device numbers with devices
+use crate::{devno::Devno, error::c_result, BlkIdResult};
+use blkid_sys::*;
+use std::{
+    ffi::CStr,
+    os::unix::io::{AsFd, AsRawFd},
+    path::PathBuf,
+};
+
+/// Size of the buffer for the kernel name of the whole disk
+const DISK_NAME_LEN: usize = 256;
+
+/// Resolves the device node (e.g. `/dev/sda1`) of `devno` by scanning `/dev`, `None` if no
+/// node is found. The same as [`Devno::to_devname`].
+pub fn devno_to_devname(devno: Devno) -> Option<PathBuf> {
+    devno.to_devname()
+}
+
+/// Returns the kernel name (e.g. `sda`) and the device number of the whole disk which contains
+/// `devno` (e.g. a partition number from the `PART_ENTRY_DISK` value). A whole disk is returned
+/// as is.
+pub fn devno_to_wholedisk(devno: Devno) -> BlkIdResult<(String, Devno)> {
+    let mut name = [0u8; DISK_NAME_LEN];
+    let mut disk_devno: libc::dev_t = 0;
+
+    unsafe {
+        c_result(blkid_devno_to_wholedisk(
+            devno.as_dev_t(),
+            name.as_mut_ptr() as *mut libc::c_char,
+            name.len(),
+            &mut disk_devno,
+        ))
+    }?;
+
+    let name = CStr::from_bytes_until_nul(&name).unwrap_or_default();
+    Ok((name.to_str()?.to_owned(), Devno::from_dev_t(disk_devno)))
+}
+
+/// Returns size of the block device or regular file in bytes, `0` if the size cannot be
+/// determined
+pub fn get_dev_size<F: AsFd>(fd: F) -> BlkIdResult<u64> {
+    let size = unsafe { c_result(blkid_get_dev_size(fd.as_fd().as_raw_fd())) }?;
+    Ok(size as u64)
+}
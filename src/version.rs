@@ -0,0 +1,96 @@
+//! Version of the `libblkid` the process is linked with
+use crate::{BlkIdError, BlkIdResult};
+use blkid_sys::*;
+use std::{
+    ffi::{CStr, CString},
+    fmt, ptr,
+};
+
+/// Runtime version of `libblkid`, which may differ from the version the crate was built against
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LibraryVersion {
+    version: String,
+    date: String,
+    code: i32,
+    major: u32,
+    minor: u32,
+    patch: u32,
+}
+
+impl LibraryVersion {
+    /// Version string (e.g. "2.39.3")
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// Release date string (e.g. "04-Dec-2023")
+    pub fn date(&self) -> &str {
+        &self.date
+    }
+
+    /// Version code used by `libblkid` (e.g. 2393 for "2.39.3")
+    pub fn code(&self) -> i32 {
+        self.code
+    }
+
+    /// Major version number
+    pub fn major(&self) -> u32 {
+        self.major
+    }
+
+    /// Minor version number
+    pub fn minor(&self) -> u32 {
+        self.minor
+    }
+
+    /// Patch version number, `0` if the version string has no patch part (e.g. "2.40")
+    pub fn patch(&self) -> u32 {
+        self.patch
+    }
+
+    /// Returns `true` if the library is the version `major.minor` or newer, e.g. `at_least(2, 37)`
+    /// for [`crate::prober::Prober::set_hint`]
+    pub fn at_least(&self, major: u32, minor: u32) -> bool {
+        (self.major, self.minor) >= (major, minor)
+    }
+}
+
+impl fmt::Display for LibraryVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "libblkid {} ({})", self.version, self.date)
+    }
+}
+
+/// Returns the version of the linked `libblkid`
+pub fn library_version() -> BlkIdResult<LibraryVersion> {
+    let mut version_ptr: *const libc::c_char = ptr::null();
+    let mut date_ptr: *const libc::c_char = ptr::null();
+
+    let code = unsafe { blkid_get_library_version(&mut version_ptr, &mut date_ptr) };
+    let version = unsafe { CStr::from_ptr(version_ptr) }.to_str()?.to_owned();
+    let date = unsafe { CStr::from_ptr(date_ptr) }.to_str()?.to_owned();
+
+    let mut numbers = version
+        .split(['.', '-'])
+        .map(|number| number.parse::<u32>());
+    let (major, minor, patch) = match (numbers.next(), numbers.next(), numbers.next()) {
+        (Some(Ok(major)), Some(Ok(minor)), Some(Ok(patch))) => (major, minor, patch),
+        (Some(Ok(major)), Some(Ok(minor)), _) => (major, minor, 0),
+        _ => return Err(BlkIdError::InvalidVersion(version)),
+    };
+
+    Ok(LibraryVersion {
+        version,
+        date,
+        code,
+        major,
+        minor,
+        patch,
+    })
+}
+
+/// Converts version string (e.g. "2.39.3") to the version code used by `libblkid` (e.g. 2393)
+pub fn parse_version_string(version: &str) -> BlkIdResult<i32> {
+    let version = CString::new(version)?;
+    Ok(unsafe { blkid_parse_version_string(version.as_ptr()) })
+}
@@ -0,0 +1,99 @@
+//! Normalization of the identifiers reported in `UUID` and `PTUUID` values
+use std::{fmt, str::FromStr};
+
+/// Identifier of a filesystem or a partition table.
+///
+/// `libblkid` reports identifiers in the native shape of every format. Parsing recognizes the
+/// shape ignoring case and optional dashes, so two values compare equal if they denote the same
+/// identifier. [`fmt::Display`] prints the canonical form used by `libblkid`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum VolumeId {
+    /// RFC 4122 UUID (e.g. ext4, xfs, btrfs, GPT), printed as lower case hex with dashes
+    Uuid([u8; 16]),
+    /// 32-bit serial number of FAT and exFAT, printed as `XXXX-XXXX`
+    FatSerial(u32),
+    /// 64-bit serial number (e.g. NTFS), printed as 16 upper case hex digits
+    NtfsSerial(u64),
+    /// DOS (MBR) disk identifier, printed as 8 lower case hex digits
+    DosDiskId(u32),
+    /// Identifier of another shape (e.g. ISO9660 creation time), compared verbatim
+    Other(String),
+}
+
+impl VolumeId {
+    /// Parses the identifier, values of unknown shape are kept as [`Self::Other`]
+    pub fn parse(value: &str) -> Self {
+        let value = value.trim();
+        let dashes = value.matches('-').count();
+        let digits = value.replace('-', "");
+        if digits.is_empty() || !digits.bytes().all(|c| c.is_ascii_hexdigit()) {
+            return Self::Other(value.to_owned());
+        }
+
+        match (digits.len(), dashes) {
+            (32, 0) | (32, 4) => {
+                let mut uuid = [0; 16];
+                for (i, byte) in uuid.iter_mut().enumerate() {
+                    *byte = u8::from_str_radix(&digits[i * 2..i * 2 + 2], 16).unwrap_or(0);
+                }
+                Self::Uuid(uuid)
+            }
+            (16, 0) => Self::NtfsSerial(u64::from_str_radix(&digits, 16).unwrap_or(0)),
+            (8, 1) => Self::FatSerial(u32::from_str_radix(&digits, 16).unwrap_or(0)),
+            (8, 0) => Self::DosDiskId(u32::from_str_radix(&digits, 16).unwrap_or(0)),
+            _ => Self::Other(value.to_owned()),
+        }
+    }
+
+    /// Returns `true` if `value` denotes the same identifier
+    pub fn matches(&self, value: &str) -> bool {
+        *self == Self::parse(value)
+    }
+
+    /// Returns `true` for RFC 4122 UUIDs
+    pub fn is_uuid(&self) -> bool {
+        matches!(self, Self::Uuid(_))
+    }
+
+    /// Raw bytes of the UUID, `None` for other shapes
+    pub fn uuid_bytes(&self) -> Option<&[u8; 16]> {
+        match self {
+            Self::Uuid(uuid) => Some(uuid),
+            _ => None,
+        }
+    }
+}
+
+impl From<&str> for VolumeId {
+    fn from(value: &str) -> Self {
+        Self::parse(value)
+    }
+}
+
+impl FromStr for VolumeId {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::parse(s))
+    }
+}
+
+impl fmt::Display for VolumeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Uuid(uuid) => {
+                for (i, byte) in uuid.iter().enumerate() {
+                    if matches!(i, 4 | 6 | 8 | 10) {
+                        write!(f, "-")?;
+                    }
+                    write!(f, "{:02x}", byte)?;
+                }
+                Ok(())
+            }
+            Self::FatSerial(serial) => write!(f, "{:04X}-{:04X}", serial >> 16, serial & 0xffff),
+            Self::NtfsSerial(serial) => write!(f, "{:016X}", serial),
+            Self::DosDiskId(id) => write!(f, "{:08x}", id),
+            Self::Other(value) => write!(f, "{}", value),
+        }
+    }
+}
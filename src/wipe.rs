@@ -0,0 +1,342 @@
+//! Signature wiping workflows built on top of [`Prober::do_wipe`]
+use crate::{
+    probe_result::ProbeResult,
+    prober::{ProbeState, Prober},
+    tag::{PartitionTag, SuperblockTag},
+    BlkIdResult, OpenFlags, PartitionsFlags, SuperblocksFlags,
+};
+use std::{io, path::Path};
+
+/// Kind of the signature derived from superblock `USAGE` or partitions chain
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SignatureKind {
+    /// Filesystem superblock
+    Filesystem,
+    /// RAID or volume manager superblock
+    Raid,
+    /// Encrypted container header
+    Crypto,
+    /// Other superblock (e.g. swap)
+    Other,
+    /// Partition table (including protective MBR)
+    PartitionTable,
+}
+
+/// Options of the wipe functions
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WipeOptions {
+    overwrite: Option<Overwrite>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Overwrite {
+    len: u64,
+    pattern: Vec<u8>,
+}
+
+impl WipeOptions {
+    /// Erases magic strings only (the same as [`Prober::do_wipe`])
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// In addition to the magic string erase, fills `len`-byte aligned block of the device which
+    /// contains the magic string with zeroes
+    pub fn zero_fill(self, len: u64) -> Self {
+        self.overwrite_with(len, Vec::new())
+    }
+
+    /// In addition to the magic string erase, fills `len`-byte aligned block of the device which
+    /// contains the magic string with repeated `pattern` (zeroes if `pattern` is empty). The data
+    /// is flushed by `fsync` before the next signature is probed.
+    ///
+    /// For example `len` 4096 covers the whole superblock of most filesystems, but note that the
+    /// block may contain other signatures too (e.g. the MBR at the begin of the device).
+    pub fn overwrite_with(mut self, len: u64, pattern: Vec<u8>) -> Self {
+        self.overwrite = Some(Overwrite { len, pattern });
+        self
+    }
+}
+
+/// Erases all superblock and partition table signatures from the device (similar to
+/// `wipefs -a`) and returns the erased signatures. See [`Prober::wipe_all`].
+///
+/// The device is opened in `O_RDWR` mode, unless `dry_run` is set.
+pub fn wipe_all_signatures<P: AsRef<Path>>(path: P, dry_run: bool) -> BlkIdResult<Vec<Signature>> {
+    let path = path.as_ref();
+    let flags = if dry_run {
+        OpenFlags::empty()
+    } else {
+        OpenFlags::READ_WRITE
+    };
+
+    Prober::open(path, flags)?
+        .wipe_all(dry_run)
+        .map_err(|err| err.with_path(path))
+}
+
+/// Signature (superblock or partition table) detected on the device
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Signature {
+    typ: String,
+    usage: Option<String>,
+    offset: u64,
+    magic: Vec<u8>,
+    label: Option<String>,
+    uuid: Option<String>,
+}
+
+impl Signature {
+    /// Creates signature from the current probing result, `None` if the result does not describe
+    /// magic string of a superblock or a partition table
+    fn from_prober(prober: &Prober, result: &ProbeResult) -> BlkIdResult<Option<Self>> {
+        let (typ, usage, offset, magic, uuid) = if let Some(typ) = result.get(SuperblockTag::Type) {
+            (
+                typ,
+                result.usage(),
+                result.get(SuperblockTag::SbmagicOffset),
+                prober.lookup_value_bytes("SBMAGIC")?,
+                result.uuid(),
+            )
+        } else if let Some(typ) = result.get(PartitionTag::Pttype) {
+            (
+                typ,
+                Some("partition table"),
+                result.get("PTMAGIC_OFFSET"),
+                prober.lookup_value_bytes("PTMAGIC")?,
+                result.pt_uuid(),
+            )
+        } else {
+            return Ok(None);
+        };
+
+        let offset = match offset.and_then(|offset| offset.parse().ok()) {
+            Some(offset) => offset,
+            None => return Ok(None),
+        };
+
+        Ok(Some(Self {
+            typ: typ.to_owned(),
+            usage: usage.map(str::to_owned),
+            offset,
+            magic: magic.unwrap_or_default(),
+            label: result.label().map(str::to_owned),
+            uuid: uuid.map(str::to_owned),
+        }))
+    }
+
+    /// Superblock type (e.g. "ext4") or partition table type (e.g. "gpt", "PMBR")
+    pub fn typ(&self) -> &str {
+        &self.typ
+    }
+
+    /// Usage string: "filesystem", "raid", "crypto", "other" or "partition table"
+    pub fn usage(&self) -> Option<&str> {
+        self.usage.as_deref()
+    }
+
+    /// Kind of the signature
+    pub fn kind(&self) -> SignatureKind {
+        match self.usage() {
+            Some("filesystem") => SignatureKind::Filesystem,
+            Some("raid") => SignatureKind::Raid,
+            Some("crypto") => SignatureKind::Crypto,
+            Some("partition table") => SignatureKind::PartitionTable,
+            _ => SignatureKind::Other,
+        }
+    }
+
+    /// Offset of the magic string in bytes, relative to the probing area
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Magic string which identifies the signature
+    pub fn magic(&self) -> &[u8] {
+        &self.magic
+    }
+
+    /// Number of bytes erased by the wipe
+    pub fn magic_len(&self) -> usize {
+        self.magic.len()
+    }
+
+    /// Filesystem label
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    /// Filesystem UUID or partition table ID
+    pub fn uuid(&self) -> Option<&str> {
+        self.uuid.as_deref()
+    }
+}
+
+impl Prober {
+    /// Returns every signature which would be erased by wiping all signatures from the device.
+    /// Nothing is written to the device.
+    ///
+    /// # Note
+    ///
+    /// The function enables the partitions chain and overwrites superblocks and partitions flags
+    /// to make the magic strings available.
+    pub fn wipe_plan(&self) -> BlkIdResult<Vec<Signature>> {
+        self.prepare_wipe()?;
+        let signatures = self.walk_signatures(true, &WipeOptions::default(), |_| true)?;
+
+        // The dry run hides the signatures in the cached buffers, make them visible again
+        #[cfg(blkid = "2.31")]
+        self.reset_buffers()?;
+
+        Ok(signatures)
+    }
+
+    /// Erases signatures accepted by `filter` and keeps the rest (similar to `wipefs -t`). Returns
+    /// erased signatures.
+    ///
+    /// The prober has to be assigned to a device opened in `O_RDWR` mode, see [`Self::do_wipe`].
+    ///
+    /// # Examples
+    ///
+    /// ```ignore, compile_fail
+    /// // Remove filesystem signatures only, keep the partition table
+    /// let wiped = prober.wipe_matching(|sig| sig.kind() == SignatureKind::Filesystem)?;
+    /// ```
+    pub fn wipe_matching<F>(&self, filter: F) -> BlkIdResult<Vec<Signature>>
+    where
+        F: FnMut(&Signature) -> bool,
+    {
+        self.wipe_matching_with(&WipeOptions::default(), filter)
+    }
+
+    /// Same as [`Self::wipe_matching`] but allows to overwrite whole signature regions, see
+    /// [`WipeOptions`]
+    pub fn wipe_matching_with<F>(
+        &self,
+        options: &WipeOptions,
+        filter: F,
+    ) -> BlkIdResult<Vec<Signature>>
+    where
+        F: FnMut(&Signature) -> bool,
+    {
+        self.prepare_wipe()?;
+        self.walk_signatures(false, options, filter)
+    }
+
+    /// Erases all signatures from the device and returns them, see [`Self::wipe_matching`]. With
+    /// `dry_run` nothing is written to the device, the same as [`Self::wipe_plan`].
+    pub fn wipe_all(&self, dry_run: bool) -> BlkIdResult<Vec<Signature>> {
+        if dry_run {
+            self.wipe_plan()
+        } else {
+            self.wipe_matching(|_| true)
+        }
+    }
+
+    /// Erases signatures of the specified kinds. See [`Self::wipe_matching`]
+    pub fn wipe_kinds(&self, kinds: &[SignatureKind]) -> BlkIdResult<Vec<Signature>> {
+        self.wipe_matching(|signature| kinds.contains(&signature.kind()))
+    }
+
+    fn prepare_wipe(&self) -> BlkIdResult<()> {
+        self.set_superblocks_flags(
+            SuperblocksFlags::MAGIC
+                | SuperblocksFlags::TYPE
+                | SuperblocksFlags::USAGE
+                | SuperblocksFlags::LABEL
+                | SuperblocksFlags::UUID,
+        )?;
+        self.enable_partitions(true)?;
+        self.set_partitions_flags(PartitionsFlags::MAGIC)
+    }
+
+    /// Runs the probing loop and wipes every detected signature accepted by `filter`. The
+    /// [`Self::do_wipe`] moves the prober one step back, so the same probing function is called
+    /// again to find another signature of the same type. In `dry_run` mode the signature is
+    /// hidden in memory only.
+    fn walk_signatures<F>(
+        &self,
+        dry_run: bool,
+        options: &WipeOptions,
+        mut filter: F,
+    ) -> BlkIdResult<Vec<Signature>>
+    where
+        F: FnMut(&Signature) -> bool,
+    {
+        let mut signatures = Vec::new();
+        self.reset();
+
+        while self.do_probe()? == ProbeState::Success {
+            let result = self.result()?;
+            match Signature::from_prober(self, &result)? {
+                Some(signature) if filter(&signature) => {
+                    self.do_wipe(dry_run)?;
+                    if let (false, Some(overwrite)) = (dry_run, &options.overwrite) {
+                        self.overwrite_region(&signature, overwrite)?;
+                    }
+                    signatures.push(signature);
+                }
+                _ => {}
+            }
+        }
+
+        Ok(signatures)
+    }
+
+    /// Fills the aligned block with the signature. It's called after [`Self::do_wipe`] which
+    /// resets the buffers, so the next probing reads the overwritten data.
+    fn overwrite_region(&self, signature: &Signature, overwrite: &Overwrite) -> BlkIdResult<()> {
+        if overwrite.len == 0 {
+            return Ok(());
+        }
+
+        let area_offset = self.get_offset()? as u64;
+        let area_size = self.get_size()? as u64;
+        let start = signature.offset - signature.offset % overwrite.len;
+        let len = overwrite.len.min(area_size.saturating_sub(start));
+        // The block is kept in memory, it cannot exceed the address space of 32-bit targets
+        let len = usize::try_from(len).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "overwritten block is too large",
+            )
+        })?;
+
+        let buf: Vec<u8> = if overwrite.pattern.is_empty() {
+            vec![0; len]
+        } else {
+            overwrite
+                .pattern
+                .iter()
+                .copied()
+                .cycle()
+                .take(len)
+                .collect()
+        };
+
+        self.write_all_at(&buf, area_offset + start)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prober::tests::{SparseImage, LARGE_SIZE};
+
+    #[test]
+    fn wipe_plan_offset_above_4_gib() {
+        // MD 0.90 superblock is stored in the last 64 KiB aligned block of the device
+        let offset = (LARGE_SIZE & !0xffff) - 0x10000;
+        let mut superblock = vec![0; 4096];
+        superblock[..4].copy_from_slice(&0xa92b_4efcu32.to_le_bytes());
+        superblock[8..12].copy_from_slice(&90u32.to_le_bytes());
+
+        let image = SparseImage::new("wipe", LARGE_SIZE);
+        image.write_at(&superblock, offset);
+
+        let signatures = image.prober().wipe_plan().unwrap();
+        assert_eq!(signatures.len(), 1);
+        assert_eq!(signatures[0].typ(), "linux_raid_member");
+        assert_eq!(signatures[0].offset(), offset);
+        assert_eq!(signatures[0].magic(), &0xa92b_4efcu32.to_le_bytes());
+    }
+}